@@ -0,0 +1,116 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use connect4::{
+    apply_action, bitboard::BitBoard, check_state, legal_moves, play, Agent, Connect4Action,
+    Connect4Check, Connect4State, MctsAgent, RandomAgent,
+};
+
+// 16 discs down, both players still have chances. The moves are 1-indexed columns, see
+// `Connect4State::from_moves`.
+fn midgame() -> Connect4State {
+    Connect4State::from_moves(include_str!("fixtures/midgame.txt").trim()).unwrap()
+}
+
+fn engine(c: &mut Criterion) {
+    let state = midgame();
+    c.bench_function("check_state", |b| b.iter(|| check_state(black_box(&state))));
+
+    c.bench_function("apply_action", |b| {
+        b.iter_batched_ref(
+            midgame,
+            |state| apply_action(state, black_box(&Connect4Action::Drop { column: 3 })),
+            BatchSize::SmallInput,
+        )
+    });
+
+    c.bench_function("random play", |b| {
+        let mut seed = 0;
+        b.iter(|| {
+            seed += 1;
+            let mut state = Connect4State::default();
+            play(
+                &mut state,
+                &mut RandomAgent::seeded(seed),
+                &mut RandomAgent::seeded(seed + 1),
+            )
+            .unwrap()
+        })
+    });
+
+    // The same random game to the end from the midgame, played on each board representation.
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut end = state.clone();
+    let mut game = vec![];
+    while let Connect4Check::InProgress = check_state(&end) {
+        let column = *legal_moves(&end).choose(&mut rng).unwrap();
+        apply_action(&mut end, &Connect4Action::Drop { column }).unwrap();
+        game.push(column);
+    }
+    let mut playouts = c.benchmark_group("random playout");
+    playouts.bench_function("Connect4State", |b| {
+        b.iter_batched_ref(
+            midgame,
+            |state| {
+                for &column in black_box(&game) {
+                    let action = Connect4Action::Drop { column };
+                    if let Connect4Check::Over(result) = apply_action(state, &action).unwrap() {
+                        return Some(result);
+                    }
+                }
+                None
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    playouts.bench_function("BitBoard", |b| {
+        b.iter_batched_ref(
+            || BitBoard::try_from(&state).unwrap(),
+            |board| {
+                for &column in black_box(&game) {
+                    board.play(column);
+                    if let Some(winner) = board.winner() {
+                        return Some(winner);
+                    }
+                    if board.is_full() {
+                        break;
+                    }
+                }
+                None
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    playouts.finish();
+
+    c.bench_function("mcts_agent turn", |b| {
+        b.iter_batched_ref(
+            || MctsAgent::seeded(1000, std::f32::consts::SQRT_2, 0),
+            |agent| agent.choose(black_box(&state)),
+            BatchSize::SmallInput,
+        )
+    });
+
+    // The same number of simulations split over more threads, to see how the search scales.
+    let mut threads = c.benchmark_group("mcts threads");
+    threads.sample_size(20);
+    for count in [1, 8] {
+        threads.bench_function(count.to_string(), |b| {
+            b.iter_batched_ref(
+                || {
+                    let mut agent = MctsAgent::seeded(8000, std::f32::consts::SQRT_2, 0);
+                    agent.threads = count;
+                    agent
+                },
+                |agent| agent.choose(black_box(&state)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    threads.finish();
+}
+
+criterion_group!(benches, engine);
+criterion_main!(benches);
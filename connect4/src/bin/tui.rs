@@ -0,0 +1,192 @@
+//! Play the MCTS bot in the terminal. Left and right move the cursor, enter or space drops a disc
+//! and `q` or escape resigns.
+
+use std::{cell::RefCell, io, rc::Rc};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    DefaultTerminal,
+};
+
+use connect4::{
+    check_action, play, Agent, Connect4Action, Connect4Result, Connect4State, MctsAgent,
+};
+
+// Disc colors for the person and the bot.
+const PLAYER_COLORS: [Color; 2] = [Color::Blue, Color::Red];
+
+// The terminal and what to show under the board, shared by both players so each can draw while
+// it's their turn.
+struct Ui {
+    terminal: DefaultTerminal,
+    cursor: usize,
+    status: String,
+}
+
+impl Ui {
+    fn draw(&mut self, state: &Connect4State, show_cursor: bool) -> io::Result<()> {
+        let mut lines = vec![];
+        let cursor = (0..state.config.cols)
+            .map(|col| {
+                let marker = if show_cursor && col == self.cursor {
+                    "▼"
+                } else {
+                    " "
+                };
+                Span::styled(
+                    format!(" {} ", marker),
+                    Style::default().fg(PLAYER_COLORS[0]),
+                )
+            })
+            .collect::<Vec<_>>();
+        lines.push(Line::from(cursor));
+        for row in (0..state.config.rows).rev() {
+            let cells = (0..state.config.cols)
+                .map(|col| {
+                    let highlight = if show_cursor && col == self.cursor {
+                        Style::default().bg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    };
+                    match state.cell(col, row) {
+                        Some(player) => Span::styled(
+                            " ● ",
+                            highlight
+                                .fg(PLAYER_COLORS[player % PLAYER_COLORS.len()])
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        None => Span::styled(" · ", highlight.fg(Color::Gray)),
+                    }
+                })
+                .collect::<Vec<_>>();
+            lines.push(Line::from(cells));
+        }
+        let numbers = (1..=state.config.cols)
+            .map(|col| Span::raw(format!(" {} ", col)))
+            .collect::<Vec<_>>();
+        lines.push(Line::from(numbers));
+        lines.push(Line::default());
+        lines.push(Line::from(self.status.as_str()));
+
+        let board = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::bordered().title(" Connect 4 "));
+        self.terminal
+            .draw(|frame| frame.render_widget(board, frame.area()))?;
+        Ok(())
+    }
+
+    // Waits for a key press, ignoring releases and everything that isn't a key.
+    fn key(&mut self) -> io::Result<KeyCode> {
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    return Ok(key.code);
+                }
+            }
+        }
+    }
+}
+
+// The person at the keyboard.
+struct Keyboard {
+    ui: Rc<RefCell<Ui>>,
+}
+
+impl Keyboard {
+    fn prompt(&mut self, state: &Connect4State) -> io::Result<Option<Connect4Action>> {
+        let mut ui = self.ui.borrow_mut();
+        loop {
+            ui.draw(state, true)?;
+            match ui.key()? {
+                KeyCode::Left => ui.cursor = ui.cursor.saturating_sub(1),
+                KeyCode::Right => ui.cursor = (ui.cursor + 1).min(state.config.cols - 1),
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    let action = Connect4Action::Drop { column: ui.cursor };
+                    if check_action(state, &action) {
+                        return Ok(Some(action));
+                    }
+                    ui.status = format!("Column {} is full.", ui.cursor + 1);
+                }
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Agent for Keyboard {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        // Resign if the terminal goes away.
+        self.prompt(state).unwrap_or(None)
+    }
+
+    fn name(&self) -> &str {
+        "You"
+    }
+}
+
+// The bot, telling the person what it's doing.
+struct Bot {
+    ui: Rc<RefCell<Ui>>,
+    agent: MctsAgent,
+}
+
+impl Agent for Bot {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        let mut ui = self.ui.borrow_mut();
+        ui.status = "MCTS is thinking...".to_string();
+        // Drawing is only for show, a broken terminal shows up on the person's turn.
+        ui.draw(state, false).ok();
+        let action = self.agent.choose(state)?;
+        let win_rate = self
+            .agent
+            .last_stats()
+            .iter()
+            .find(|stats| stats.action == action)
+            .map_or(0., |stats| stats.win_rate);
+        ui.status = format!(
+            "MCTS played {} and thinks it wins {:.0}% of the time, searched {}.",
+            action.column() + 1,
+            win_rate * 100.,
+            self.agent.search_stats()
+        );
+        Some(action)
+    }
+
+    fn name(&self) -> &str {
+        "MCTS"
+    }
+}
+
+fn main() -> io::Result<()> {
+    let ui = Rc::new(RefCell::new(Ui {
+        terminal: ratatui::init(),
+        cursor: 3,
+        status: "Your move, you're blue.".to_string(),
+    }));
+    let mut keyboard = Keyboard { ui: ui.clone() };
+    let mut bot = Bot {
+        ui: ui.clone(),
+        agent: MctsAgent::default(),
+    };
+
+    let mut state = Connect4State::default();
+    let result = play(&mut state, &mut keyboard, &mut bot);
+    let mut ui = ui.borrow_mut();
+    ui.status = match &result {
+        Ok(outcome) => match outcome.result {
+            Connect4Result::Winner { player: 0, .. } => "You win!".to_string(),
+            Connect4Result::Winner { .. } => "MCTS wins.".to_string(),
+            Connect4Result::Tie => "It's a tie.".to_string(),
+        },
+        Err(error) => error.to_string(),
+    } + " Press any key to quit.";
+    let shown = ui.draw(&state, false).and_then(|()| ui.key());
+    ratatui::restore();
+    shown.map(|_| ())
+}
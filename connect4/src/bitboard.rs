@@ -0,0 +1,180 @@
+use thiserror::Error;
+
+use crate::{BoardConfig, Connect4State, COLS, ROWS};
+
+// Each column gets an extra bit on top that is always empty, so shifting a line off the top of
+// one column can't wrap around into the bottom of the next.
+pub(crate) const HEIGHT: usize = ROWS + 1;
+pub(crate) const BOTTOM_ROW: u64 = {
+    let mut mask = 0;
+    let mut col = 0;
+    while col < COLS {
+        mask |= 1 << (col * HEIGHT);
+        col += 1;
+    }
+    mask
+};
+
+#[derive(Error, Debug)]
+pub enum BitBoardError {
+    #[error("Bitboards only support the standard 6x7 connect 4 board. Got `{0:?}`.")]
+    UnsupportedBoard(BoardConfig),
+}
+
+/// The standard board packed into two `u64`s, one per player.
+///
+/// Bit `col * 7 + row` is set if the player has a disc at (col, row). The 7th bit of every column
+/// is never set, the gaps let `is_win` use shifts without false positives across columns.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    pub players: [u64; 2],
+    pub next_player: usize,
+}
+
+impl BitBoard {
+    /// Every occupied cell.
+    pub fn occupied(&self) -> u64 {
+        self.players[0] | self.players[1]
+    }
+
+    fn top_cell(col: usize) -> u64 {
+        1 << (col * HEIGHT + ROWS - 1)
+    }
+
+    pub fn can_play(&self, col: usize) -> bool {
+        col < COLS && self.occupied() & Self::top_cell(col) == 0
+    }
+
+    /// Drops a disc for `next_player` in `col`. The caller checks `can_play` first.
+    pub fn play(&mut self, col: usize) {
+        let occupied = self.occupied();
+        // Adding the bottom bit of the column to the occupied bits carries up to the first empty
+        // cell.
+        let column = ((1 << HEIGHT) - 1) << (col * HEIGHT);
+        let cell = (occupied + (1 << (col * HEIGHT))) & column & !occupied;
+        self.players[self.next_player] |= cell;
+        self.next_player = 1 - self.next_player;
+    }
+
+    /// True if `mask` has four in a row in any direction.
+    pub fn is_win(mask: u64) -> bool {
+        // Vertical, horizontal, diagonal down and diagonal up.
+        for shift in [1, HEIGHT, HEIGHT - 1, HEIGHT + 1] {
+            let pairs = mask & (mask >> shift);
+            if pairs & (pairs >> (2 * shift)) != 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        (0..2).find(|&player| Self::is_win(self.players[player]))
+    }
+
+    pub fn is_full(&self) -> bool {
+        // The top cell of every column is the bottom row shifted up.
+        let top_row = BOTTOM_ROW << (ROWS - 1);
+        self.occupied() & top_row == top_row
+    }
+}
+
+impl TryFrom<&Connect4State> for BitBoard {
+    type Error = BitBoardError;
+
+    fn try_from(state: &Connect4State) -> Result<Self, Self::Error> {
+        if state.config != BoardConfig::default() {
+            return Err(BitBoardError::UnsupportedBoard(state.config));
+        }
+        let mut players = [0, 0];
+        for col in 0..COLS {
+            for row in 0..ROWS {
+                if let Some(player) = state.cell(col, row) {
+                    players[player] |= 1 << (col * HEIGHT + row);
+                }
+            }
+        }
+        Ok(Self {
+            players,
+            next_player: state.next_player,
+        })
+    }
+}
+
+/// The order moves were played in isn't stored on a bitboard, so the state's `history` is empty.
+impl From<&BitBoard> for Connect4State {
+    fn from(bitboard: &BitBoard) -> Self {
+        let mut state = Connect4State::default();
+        for col in 0..COLS {
+            for row in 0..ROWS {
+                let bit = 1 << (col * HEIGHT + row);
+                for player in 0..2 {
+                    if bitboard.players[player] & bit != 0 {
+                        state.board[col * ROWS + row] = Some(player);
+                    }
+                }
+            }
+        }
+        state.next_player = bitboard.next_player;
+        state.recount();
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply_action, check_state, Agent, Connect4Action, Connect4Check, RandomAgent};
+
+    #[test]
+    fn matches_check_state_on_random_games() {
+        for seed in 0..100 {
+            let mut agent = RandomAgent::seeded(seed);
+            let mut state = Connect4State::default();
+            let mut bitboard = BitBoard::default();
+            loop {
+                let action = agent.choose(&state).unwrap();
+                assert!(bitboard.can_play(action.column()));
+                bitboard.play(action.column());
+                let check = apply_action(&mut state, &action).unwrap();
+
+                assert_eq!(BitBoard::try_from(&state).unwrap(), bitboard);
+                assert_eq!(Connect4State::from(&bitboard), state);
+                assert_eq!(state.is_full(), bitboard.is_full());
+                assert_eq!(Connect4State::from(&bitboard).heights, state.heights);
+                match check {
+                    Connect4Check::InProgress => {
+                        assert_eq!(bitboard.winner(), None);
+                        assert!(!bitboard.is_full());
+                    }
+                    Connect4Check::Over(crate::Connect4Result::Winner { player, .. }) => {
+                        assert_eq!(bitboard.winner(), Some(player));
+                        break;
+                    }
+                    Connect4Check::Over(crate::Connect4Result::Tie) => {
+                        assert_eq!(bitboard.winner(), None);
+                        assert!(bitboard.is_full());
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_column() {
+        let mut bitboard = BitBoard::default();
+        for _ in 0..ROWS {
+            assert!(bitboard.can_play(0));
+            bitboard.play(0);
+        }
+        assert!(!bitboard.can_play(0));
+        assert!(!bitboard.can_play(COLS));
+        let state = Connect4State::from(&bitboard);
+        assert!(!crate::check_action(
+            &state,
+            &Connect4Action::Drop { column: 0 }
+        ));
+        assert!(matches!(check_state(&state), Connect4Check::InProgress));
+    }
+}
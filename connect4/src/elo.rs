@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::{
+    tournament::{run_tournament, TournamentResult},
+    Agent, Connect4Result,
+};
+
+/// The rating an agent starts at before it's played anyone.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// The score `a` is expected to get against `b` given their ratings, 1 for a win, 0.5 for a tie
+/// and 0 for a loss. A 400 point gap makes the stronger side 10 times as likely to win.
+pub fn expected_score(a: f64, b: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+}
+
+/// Elo ratings for agents by name. After each game both agents move `k` times the difference
+/// between the score they got and the score they were expected to get, so a bigger `k` reacts
+/// faster to results and settles less.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ratings {
+    pub k: f64,
+    ratings: HashMap<String, f64>,
+}
+
+impl Ratings {
+    pub fn new(k: f64) -> Self {
+        Self {
+            k,
+            ratings: HashMap::new(),
+        }
+    }
+
+    /// `INITIAL_RATING` for agents that haven't played yet.
+    pub fn rating(&self, name: &str) -> f64 {
+        self.ratings.get(name).copied().unwrap_or(INITIAL_RATING)
+    }
+
+    /// Updates the ratings after a game between `a` playing player 0 and `b` playing player 1.
+    pub fn record_game(&mut self, a: &str, b: &str, result: &Connect4Result) {
+        let score = match result {
+            Connect4Result::Winner { player: 0, .. } => 1.0,
+            Connect4Result::Winner { .. } => 0.0,
+            Connect4Result::Tie => 0.5,
+        };
+        self.update(a, b, score, 1);
+    }
+
+    /// Updates the ratings after every game of a tournament at once. The expected scores all
+    /// come from the ratings before the tournament, so the order the games finished in doesn't
+    /// matter.
+    pub fn record_tournament(&mut self, result: &TournamentResult) {
+        let score = result.a_wins as f64 + 0.5 * result.ties as f64;
+        self.update(&result.a_name, &result.b_name, score, result.games());
+    }
+
+    // Moves `a` and `b` after `games` games where `a` scored `score` in total.
+    fn update(&mut self, a: &str, b: &str, score: f64, games: usize) {
+        let (rating_a, rating_b) = (self.rating(a), self.rating(b));
+        let change = self.k * (score - games as f64 * expected_score(rating_a, rating_b));
+        self.ratings.insert(a.to_string(), rating_a + change);
+        self.ratings.insert(b.to_string(), rating_b - change);
+    }
+
+    /// Every agent that's played with its rating, the strongest first.
+    pub fn ranking(&self) -> Vec<(String, f64)> {
+        let mut ranking: Vec<_> = self
+            .ratings
+            .iter()
+            .map(|(name, &rating)| (name.clone(), rating))
+            .collect();
+        ranking.sort_by(|(a_name, a), (b_name, b)| b.total_cmp(a).then(a_name.cmp(b_name)));
+        ranking
+    }
+}
+
+/// K of 32, the usual value for players without many games.
+impl Default for Ratings {
+    fn default() -> Self {
+        Self::new(32.0)
+    }
+}
+
+/// Rates agents from `(a, b, result)` games played in that order, with `a` as player 0.
+pub fn rate_games(games: &[(&str, &str, Connect4Result)], k: f64) -> Ratings {
+    let mut ratings = Ratings::new(k);
+    for (a, b, result) in games {
+        ratings.record_game(a, b, result);
+    }
+    ratings
+}
+
+/// Builds an agent for game `i`, see `run_tournament`.
+pub type MakeAgent<'a> = &'a (dyn Fn(usize) -> Box<dyn Agent> + Sync);
+
+/// Plays a tournament of `games` games between every pair of agents in `pool` and rates them on
+/// the results. Agents are named by their `Agent::name`, so give agents of the same type
+/// different names with `Agent::named`.
+pub fn round_robin(pool: &[MakeAgent], games: usize, k: f64) -> Ratings {
+    let mut ratings = Ratings::new(k);
+    for (i, make_a) in pool.iter().enumerate() {
+        for make_b in &pool[i + 1..] {
+            ratings.record_tournament(&run_tournament(make_a, make_b, games));
+        }
+    }
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GreedyAgent, RandomAgent};
+
+    #[test]
+    fn upsets_move_ratings_more() {
+        assert_eq!(expected_score(1500.0, 1500.0), 0.5);
+        assert!((expected_score(1900.0, 1500.0) - 10.0 / 11.0).abs() < 1e-9);
+
+        let win = Connect4Result::Winner {
+            player: 0,
+            line: vec![],
+        };
+        let ratings = rate_games(&[("a", "b", win.clone())], 32.0);
+        assert_eq!(ratings.rating("a"), 1516.0);
+        assert_eq!(ratings.rating("b"), 1484.0);
+
+        // Beating an agent that just lost is expected, so it's worth less.
+        let ratings = rate_games(&[("a", "b", win.clone()), ("a", "b", win)], 32.0);
+        assert!(ratings.rating("a") - 1516.0 < 16.0);
+        assert_eq!(ratings.rating("a") + ratings.rating("b"), 3000.0);
+        assert_eq!(ratings.rating("c"), INITIAL_RATING);
+    }
+
+    #[test]
+    fn ties_pull_ratings_together() {
+        let mut ratings = Ratings::default();
+        ratings.record_game("a", "b", &Connect4Result::Tie);
+        assert_eq!(ratings.rating("a"), INITIAL_RATING);
+        ratings.record_tournament(&TournamentResult {
+            a_name: "a".to_string(),
+            b_name: "b".to_string(),
+            a_wins: 3,
+            b_wins: 1,
+            ties: 0,
+        });
+        let gap = ratings.rating("a") - ratings.rating("b");
+        ratings.record_game("a", "b", &Connect4Result::Tie);
+        assert!(ratings.rating("a") - ratings.rating("b") < gap);
+    }
+
+    #[test]
+    fn ranks_a_round_robin() {
+        let greedy: MakeAgent = &|_| Box::new(GreedyAgent);
+        let random: MakeAgent = &|i| Box::new(RandomAgent::seeded(i as u64));
+        let other_random: MakeAgent =
+            &|i| Box::new(RandomAgent::seeded(1000 + i as u64).named("OtherRandom"));
+        let ratings = round_robin(&[random, greedy, other_random], 20, 16.0);
+        let ranking = ratings.ranking();
+        assert_eq!(ranking.len(), 3);
+        assert_eq!(ranking[0].0, "GreedyAgent");
+        let total: f64 = ranking.iter().map(|(_, rating)| rating).sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,139 @@
+use std::ops::Range;
+
+use crate::{check_state, BoardConfig, Connect4Check, Connect4Result, Connect4State};
+
+/// Score of a won position. Every heuristic score is far smaller than this.
+pub const WIN_SCORE: f32 = 1_000_000.;
+
+/// A line that needs one more disc.
+pub const THREE_SCORE: f32 = 5.;
+/// A line that needs two more discs.
+pub const TWO_SCORE: f32 = 2.;
+/// Each disc in the center column.
+pub const CENTER_SCORE: f32 = 3.;
+
+// The middle column, or both middle columns on even width boards.
+pub(crate) fn center_columns(cols: usize) -> Range<usize> {
+    if cols.is_multiple_of(2) {
+        cols / 2 - 1..cols / 2 + 1
+    } else {
+        cols / 2..cols / 2 + 1
+    }
+}
+
+// Every window of `connect` cells in all four directions that has discs from only one player, as
+// that player and how many discs they have in it.
+pub(crate) fn windows(state: &Connect4State) -> Vec<(usize, usize)> {
+    let BoardConfig {
+        rows,
+        cols,
+        connect,
+        ..
+    } = state.config;
+    let mut windows = vec![];
+    for (d_col, d_row) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+        for col in 0..cols {
+            for row in 0..rows {
+                let end_col = col as isize + d_col * (connect as isize - 1);
+                let end_row = row as isize + d_row * (connect as isize - 1);
+                if end_col >= cols as isize || end_row < 0 || end_row >= rows as isize {
+                    continue;
+                }
+                // Which player has discs in this window and how many, `mixed` if both do.
+                let mut owner = None;
+                let mut discs = 0;
+                let mut mixed = false;
+                for i in 0..connect as isize {
+                    let c = (col as isize + d_col * i) as usize;
+                    let r = (row as isize + d_row * i) as usize;
+                    if let Some(p) = state.cell(c, r) {
+                        if owner.is_some_and(|o| o != p) {
+                            mixed = true;
+                            break;
+                        }
+                        owner = Some(p);
+                        discs += 1;
+                    }
+                }
+                if let (Some(owner), false) = (owner, mixed) {
+                    windows.push((owner, discs));
+                }
+            }
+        }
+    }
+    windows
+}
+
+/// Scores `state` from `player`'s point of view, positive is good for them.
+///
+/// Won and lost games score `WIN_SCORE` and `-WIN_SCORE`, ties score 0. Otherwise every window of
+/// `connect` cells (in all four directions) that only holds one player's discs and empty cells
+/// scores `THREE_SCORE` if it is one disc short of a win and `TWO_SCORE` if it is two short, added
+/// for `player`'s windows and subtracted for the other player's. Each disc in the center column
+/// (both middle columns on even width boards) is worth `CENTER_SCORE` the same way.
+pub fn evaluate(state: &Connect4State, player: usize) -> f32 {
+    match check_state(state) {
+        Connect4Check::Over(Connect4Result::Winner { player: winner, .. }) => {
+            return if winner == player {
+                WIN_SCORE
+            } else {
+                -WIN_SCORE
+            };
+        }
+        Connect4Check::Over(Connect4Result::Tie) => return 0.,
+        Connect4Check::InProgress => (),
+    }
+
+    let sign = |owner: usize| if owner == player { 1. } else { -1. };
+    let mut score = 0.;
+    for col in center_columns(state.config.cols) {
+        for row in 0..state.config.rows {
+            if let Some(owner) = state.cell(col, row) {
+                score += sign(owner) * CENTER_SCORE;
+            }
+        }
+    }
+    let connect = state.config.connect;
+    for (owner, discs) in windows(state) {
+        if discs + 1 == connect {
+            score += sign(owner) * THREE_SCORE;
+        } else if discs + 2 == connect {
+            score += sign(owner) * TWO_SCORE;
+        }
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_scores() {
+        let won = Connect4State::from_moves("1122334").unwrap();
+        assert_eq!(evaluate(&won, 0), WIN_SCORE);
+        assert_eq!(evaluate(&won, 1), -WIN_SCORE);
+    }
+
+    #[test]
+    fn zero_sum() {
+        let state = Connect4State::from_moves("4453").unwrap();
+        assert_eq!(evaluate(&state, 0), -evaluate(&state, 1));
+        assert_eq!(evaluate(&Connect4State::default(), 0), 0.);
+    }
+
+    #[test]
+    fn center_beats_edge() {
+        let center = Connect4State::from_moves("4").unwrap();
+        let edge = Connect4State::from_moves("1").unwrap();
+        assert!(evaluate(&center, 0) > evaluate(&edge, 0));
+    }
+
+    #[test]
+    fn open_three() {
+        // Player 0 builds along the bottom row while player 1 stacks in the corner.
+        let three = Connect4State::from_moves("27374").unwrap();
+        let two = Connect4State::from_moves("273").unwrap();
+        assert!(evaluate(&three, 0) > evaluate(&two, 0));
+    }
+}
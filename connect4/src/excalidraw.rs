@@ -0,0 +1,306 @@
+use exdraw::{Color, Element, Ellipse, ExcalidrawFile, Rectangle, Text};
+
+use crate::{apply_action, mcts::ActionStats, ActionError, Connect4Action, Connect4State};
+
+const CELL: f64 = 60.0;
+const DISC: f64 = 50.0;
+const PADDING: f64 = (CELL - DISC) / 2.0;
+// Space between the boards of a game and for the labels around them.
+const GAP: f64 = 40.0;
+const FONT_SIZE: f64 = 20.0;
+// Height of the bar for the most visited move.
+const BAR_HEIGHT: f64 = 120.0;
+
+const FRAME_STROKE: Color = Color::BLACK;
+const FRAME_BACKGROUND: Color = Color::from_static("#e9ecef");
+const EMPTY_STROKE: Color = Color::from_static("#868e96");
+// (stroke, background) for each player, going round again if there are more players than colors.
+const PLAYER_COLORS: [(Color, Color); 4] = [
+    (Color::BLUE, Color::from_static("#4dabf7")),
+    (Color::RED, Color::from_static("#ff8787")),
+    (Color::GREEN, Color::from_static("#69db7c")),
+    (Color::ORANGE, Color::from_static("#ffd43b")),
+];
+
+/// Draws the board as a frame with one circle per cell. Discs are filled with their player's
+/// color and empty cells are just outlined. Row 0 is at the bottom like it is on the board.
+pub fn connect4_to_excalidraw(state: &Connect4State) -> ExcalidrawFile {
+    ExcalidrawFile {
+        elements: board_elements(state, "", 0.0, 0.0),
+        ..Default::default()
+    }
+}
+
+/// Draws every position of the game left to right, starting with the board before its first move,
+/// by replaying the state's history. Each board is titled with its move number and the move that
+/// was played and has its column numbers underneath. A state whose history doesn't lead to its
+/// board, like one with its board set by hand, is drawn on its own like `connect4_to_excalidraw`.
+pub fn game_to_excalidraw(state: &Connect4State) -> ExcalidrawFile {
+    let start = Connect4State::with_first_player(state.config, state.first_player());
+    let mut end = start.clone();
+    for action in &state.history {
+        if apply_action(&mut end, action).is_err() {
+            return connect4_to_excalidraw(state);
+        }
+    }
+    match line_to_excalidraw(&start, &state.history) {
+        Ok(file) if end == *state => file,
+        _ => connect4_to_excalidraw(state),
+    }
+}
+
+/// Draws `start` and then the position after each of `moves` left to right, like
+/// `game_to_excalidraw`. Moves are numbered on from `start`'s history, which makes it good for
+/// showing the line a search expects from a position in a game. Errors if a move is illegal.
+pub fn line_to_excalidraw(
+    start: &Connect4State,
+    moves: &[Connect4Action],
+) -> Result<ExcalidrawFile, ActionError> {
+    let width = start.config.cols as f64 * CELL;
+    let height = start.config.rows as f64 * CELL;
+    let mut elements = vec![];
+    let mut position = start.clone();
+    for i in 0..=moves.len() {
+        if i > 0 {
+            apply_action(&mut position, &moves[i - 1])?;
+        }
+        let played = position.history.len();
+        let prefix = format!("move-{}-", played);
+        let x = i as f64 * (width + GAP);
+        let title = match position.history.last() {
+            None => "Start".to_string(),
+            Some(Connect4Action::Drop { column }) => format!("{}. Drop {}", played, column + 1),
+            Some(Connect4Action::PopOut { column }) => format!("{}. Pop {}", played, column + 1),
+        };
+        elements.push(text(format!("{}title", prefix), x, 0.0, title));
+        elements.extend(board_elements(&position, &prefix, x, GAP));
+        for col in 0..start.config.cols {
+            elements.push(text(
+                format!("{}label-{}", prefix, col),
+                x + col as f64 * CELL + CELL / 2.0 - FONT_SIZE / 4.0,
+                GAP + height + PADDING,
+                (col + 1).to_string(),
+            ));
+        }
+    }
+    Ok(ExcalidrawFile {
+        elements,
+        ..Default::default()
+    })
+}
+
+/// Draws the board with a bar chart of the search over it. Each drop gets a bar over its column
+/// as tall as its share of the visits, colored by the player to move and labeled with its win
+/// rate. Pop outs aren't drawn.
+pub fn mcts_stats_to_excalidraw(state: &Connect4State, stats: &[ActionStats]) -> ExcalidrawFile {
+    let chart_height = BAR_HEIGHT + GAP;
+    let mut elements = board_elements(state, "", 0.0, chart_height);
+    // At least one, so a search that visited nothing gets flat bars instead of NaN ones.
+    let max_visits = stats
+        .iter()
+        .map(|stats| stats.visits)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let (stroke, background) = &PLAYER_COLORS[state.next_player % PLAYER_COLORS.len()];
+    for stats in stats {
+        let Connect4Action::Drop { column } = stats.action else {
+            continue;
+        };
+        let height = BAR_HEIGHT * stats.visits as f64 / max_visits as f64;
+        let x = column as f64 * CELL + PADDING;
+        let bar = Rectangle::new(x, chart_height - height, DISC, height)
+            .id(format!("bar-{}", column))
+            .stroke_color(stroke.clone())
+            .background(background.clone());
+        elements.push(bar.build());
+        elements.push(text(
+            format!("win-rate-{}", column),
+            x,
+            chart_height - height - GAP,
+            format!("{:.0}%", stats.win_rate * 100.),
+        ));
+    }
+    ExcalidrawFile {
+        elements,
+        ..Default::default()
+    }
+}
+
+// The frame and cells of one board with its top left corner at (x, y). Ids start with `prefix` so
+// several boards can share a scene.
+fn board_elements(state: &Connect4State, prefix: &str, x: f64, y: f64) -> Vec<Element> {
+    let rows = state.config.rows;
+    let cols = state.config.cols;
+    let frame = Rectangle::new(x, y, cols as f64 * CELL, rows as f64 * CELL)
+        .id(format!("{}frame", prefix))
+        .stroke_color(FRAME_STROKE)
+        .background(FRAME_BACKGROUND);
+    let mut elements = vec![frame.build()];
+    for col in 0..cols {
+        for row in 0..rows {
+            let (stroke, background) = match state.cell(col, row) {
+                Some(player) => PLAYER_COLORS[player % PLAYER_COLORS.len()].clone(),
+                None => (EMPTY_STROKE, Color::TRANSPARENT),
+            };
+            let cell_x = x + col as f64 * CELL + PADDING;
+            let cell_y = y + (rows - 1 - row) as f64 * CELL + PADDING;
+            let cell = Ellipse::new(cell_x, cell_y, DISC, DISC)
+                .id(format!("{}cell-{}-{}", prefix, col, row))
+                .stroke_color(stroke)
+                .background(background);
+            elements.push(cell.build());
+        }
+    }
+    elements
+}
+
+fn text(id: String, x: f64, y: f64, text: String) -> Element {
+    Text::new(x, y, text).id(id).font_size(FONT_SIZE).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Agent, MinimaxAgent};
+    use exdraw::ElementKind;
+
+    #[test]
+    fn one_circle_per_cell() {
+        let state = Connect4State::from_moves("44").unwrap();
+        let file = connect4_to_excalidraw(&state);
+        assert_eq!(file.elements.len(), 1 + 6 * 7);
+        assert_eq!(file.elements[0].kind, ElementKind::Rectangle);
+
+        let disc = |col, row| {
+            let id = format!("cell-{}-{}", col, row);
+            file.elements.iter().find(|e| e.id == id).unwrap()
+        };
+        assert_eq!(disc(3, 0).background_color, PLAYER_COLORS[0].1.as_str());
+        assert_eq!(disc(3, 1).background_color, PLAYER_COLORS[1].1.as_str());
+        assert_eq!(disc(3, 2).background_color, Color::TRANSPARENT.as_str());
+        // The bottom row is drawn lowest.
+        assert!(disc(3, 0).y > disc(3, 1).y);
+    }
+
+    #[test]
+    fn one_board_per_move() {
+        let state = Connect4State::from_moves("445").unwrap();
+        let file = game_to_excalidraw(&state);
+        let frames: Vec<_> = file
+            .elements
+            .iter()
+            .filter(|e| e.kind == ElementKind::Rectangle)
+            .collect();
+        assert_eq!(frames.len(), 4);
+        assert!(frames.windows(2).all(|w| w[0].x < w[1].x));
+
+        let titles: Vec<_> = file
+            .elements
+            .iter()
+            .filter(|e| e.id.ends_with("title"))
+            .map(|e| match &e.kind {
+                ElementKind::Text(text) => text.text.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(titles, ["Start", "1. Drop 4", "2. Drop 4", "3. Drop 5"]);
+
+        // The last board matches the final position.
+        let last = file.elements.iter().find(|e| e.id == "move-3-cell-4-0");
+        assert_eq!(last.unwrap().background_color, PLAYER_COLORS[0].1.as_str());
+        let ids: std::collections::HashSet<_> = file.elements.iter().map(|e| &e.id).collect();
+        assert_eq!(ids.len(), file.elements.len());
+    }
+
+    #[test]
+    fn draws_a_board_set_by_hand_on_its_own() {
+        let mut state = Connect4State::from_moves("44").unwrap();
+        state.board[0] = Some(0);
+        state.recount();
+        let ids =
+            |file: ExcalidrawFile| -> Vec<_> { file.elements.into_iter().map(|e| e.id).collect() };
+        let alone = ids(connect4_to_excalidraw(&state));
+        assert_eq!(ids(game_to_excalidraw(&state)), alone);
+
+        // A history that can't be played on the board at all.
+        state.history = vec![Connect4Action::PopOut { column: 3 }];
+        assert_eq!(ids(game_to_excalidraw(&state)), alone);
+    }
+
+    #[test]
+    fn draws_the_line_from_the_position() {
+        let state = Connect4State::from_moves("3747").unwrap();
+        let mut agent = MinimaxAgent::new(5);
+        agent.choose(&state);
+        let line: Vec<_> = agent
+            .principal_variation()
+            .iter()
+            .map(|&column| Connect4Action::Drop { column })
+            .collect();
+        let file = line_to_excalidraw(&state, &line).unwrap();
+        let frames = file
+            .elements
+            .iter()
+            .filter(|e| e.kind == ElementKind::Rectangle)
+            .count();
+        assert_eq!(frames, line.len() + 1);
+        // Numbering carries on from the game so far.
+        assert!(file.elements.iter().any(|e| e.id == "move-4-title"));
+        assert!(file.elements.iter().any(|e| e.id == "move-7-title"));
+
+        let full = Connect4State::from_moves("111111").unwrap();
+        let illegal = [Connect4Action::Drop { column: 0 }];
+        assert!(line_to_excalidraw(&full, &illegal).is_err());
+    }
+
+    #[test]
+    fn second_player_starts_the_game() {
+        let mut state = Connect4State::with_first_player(Default::default(), 1);
+        apply_action(&mut state, &Connect4Action::Drop { column: 3 }).unwrap();
+        let file = game_to_excalidraw(&state);
+        let disc = file.elements.iter().find(|e| e.id == "move-1-cell-3-0");
+        assert_eq!(disc.unwrap().background_color, PLAYER_COLORS[1].1.as_str());
+    }
+
+    #[test]
+    fn bars_follow_the_visits() {
+        let state = Connect4State::default();
+        let stats = [
+            ActionStats {
+                action: Connect4Action::Drop { column: 2 },
+                visits: 10,
+                win_rate: 0.25,
+            },
+            ActionStats {
+                action: Connect4Action::Drop { column: 3 },
+                visits: 40,
+                win_rate: 0.5,
+            },
+        ];
+        let file = mcts_stats_to_excalidraw(&state, &stats);
+        let find = |id: &str| file.elements.iter().find(|e| e.id == id).unwrap();
+        assert_eq!(find("bar-3").height, BAR_HEIGHT);
+        assert_eq!(find("bar-2").height, BAR_HEIGHT / 4.0);
+        // Bars stand on the board.
+        assert_eq!(find("bar-2").y + find("bar-2").height, find("frame").y);
+        match &find("win-rate-2").kind {
+            ElementKind::Text(text) => assert_eq!(text.text, "25%"),
+            _ => panic!("win rate isn't text"),
+        }
+    }
+
+    #[test]
+    fn unvisited_bars_are_flat() {
+        let stats = [ActionStats {
+            action: Connect4Action::Drop { column: 0 },
+            visits: 0,
+            win_rate: 0.,
+        }];
+        let file = mcts_stats_to_excalidraw(&Connect4State::default(), &stats);
+        let bar = file.elements.iter().find(|e| e.id == "bar-0").unwrap();
+        assert_eq!((bar.y, bar.height), (BAR_HEIGHT + GAP, 0.));
+        let json = serde_json::to_value(bar).unwrap();
+        assert!(json["y"].is_number() && json["height"].is_number());
+    }
+}
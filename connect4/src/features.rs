@@ -0,0 +1,79 @@
+use crate::{
+    eval::{center_columns, windows},
+    Connect4State,
+};
+
+/// How many features `features` adds after the two planes.
+pub const SUMMARY_FEATURES: usize = 5;
+
+/// The board as two planes of `rows * cols` cells, 1 where there's a disc and 0 where there
+/// isn't. The first plane is `player`'s discs and the second is the other player's. Each plane is
+/// in the same order as `Connect4State::board`, column by column with the bottom of each column
+/// first, so the disc at (col, row) is at `plane * rows * cols + col * rows + row`.
+pub fn planes(state: &Connect4State, player: usize) -> Vec<f32> {
+    let cells = state.board.len();
+    let mut planes = vec![0.; 2 * cells];
+    for (i, cell) in state.board.iter().enumerate() {
+        match cell {
+            Some(owner) if *owner == player => planes[i] = 1.,
+            Some(_) => planes[cells + i] = 1.,
+            None => {}
+        }
+    }
+    planes
+}
+
+/// A fixed length description of the position from `player`'s point of view, for learned and
+/// heuristic agents. The order won't change, it's
+///
+/// 1. the two `planes`, `2 * rows * cols` values,
+/// 2. how many of `player`'s discs are in the center column (both middle columns on even width
+///    boards),
+/// 3. the same for the other player,
+/// 4. how many open threes `player` has, lines of `connect` cells that have every disc but one
+///    and the rest empty,
+/// 5. the same for the other player,
+/// 6. 1 if it's `player`'s turn and 0 if it isn't.
+pub fn features(state: &Connect4State, player: usize) -> Vec<f32> {
+    let mut features = planes(state, player);
+    let mut centers = [0.; 2];
+    for col in center_columns(state.config.cols) {
+        for row in 0..state.config.rows {
+            if let Some(owner) = state.cell(col, row) {
+                centers[usize::from(owner != player)] += 1.;
+            }
+        }
+    }
+    let mut threes = [0.; 2];
+    for (owner, discs) in windows(state) {
+        if discs + 1 == state.config.connect {
+            threes[usize::from(owner != player)] += 1.;
+        }
+    }
+    let to_move = if state.next_player == player { 1. } else { 0. };
+    features.extend(centers);
+    features.extend(threes);
+    features.push(to_move);
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        // Player 0 has three along the bottom with both ends open and player 1 has two discs on
+        // top of them in the center column. It's player 1's turn.
+        let state = Connect4State::from_moves("44345").unwrap();
+        let features = features(&state, 0);
+        assert_eq!(features.len(), 2 * 42 + SUMMARY_FEATURES);
+        assert_eq!(features[..42].iter().sum::<f32>(), 3.);
+        assert_eq!(features[42..84].iter().sum::<f32>(), 2.);
+        assert_eq!(features[84..], [1., 2., 2., 0., 0.]);
+
+        let other = super::features(&state, 1);
+        assert_eq!(other[..42], features[42..84]);
+        assert_eq!(other[84..], [2., 1., 0., 2., 1.]);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{legal_moves, threats::immediate_threats, Agent, Connect4Action, Connect4State};
+
+/// Looks one move ahead. Wins if it can, otherwise blocks the opponent's winning move, otherwise
+/// plays as close to the center as possible. With more than two players it blocks whoever's turn
+/// is soonest first.
+pub struct GreedyAgent;
+
+impl Agent for GreedyAgent {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        let player = state.next_player;
+        if let Some(&column) = immediate_threats(state, player).first() {
+            return Some(Connect4Action::Drop { column });
+        }
+        let mut opponent = state.config.player_after(player);
+        while opponent != player {
+            if let Some(&column) = immediate_threats(state, opponent).first() {
+                return Some(Connect4Action::Drop { column });
+            }
+            opponent = state.config.player_after(opponent);
+        }
+        let center = state.config.cols / 2;
+        let column = legal_moves(state)
+            .into_iter()
+            .min_by_key(|&column| column.abs_diff(center))?;
+        Some(Connect4Action::Drop { column })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply_action, check_state, Connect4Check, Connect4Result, RandomAgent};
+
+    #[test]
+    fn always_takes_the_win() {
+        for seed in 0..200 {
+            let mut random = RandomAgent::seeded(seed);
+            let mut state = Connect4State::default();
+            while check_state(&state) == Connect4Check::InProgress {
+                let wins = immediate_threats(&state, state.next_player);
+                let action = GreedyAgent.choose(&state).unwrap();
+                if !wins.is_empty() {
+                    assert!(wins.contains(&action.column()));
+                    let check = apply_action(&mut state, &action).unwrap();
+                    assert!(matches!(
+                        check,
+                        Connect4Check::Over(Connect4Result::Winner { .. })
+                    ));
+                    break;
+                }
+                let action = random.choose(&state).unwrap();
+                apply_action(&mut state, &action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn blocks() {
+        // Player 1 threatens to finish the bottom row in column 4 (1-indexed).
+        let state = Connect4State::from_moves("717263").unwrap();
+        assert_eq!(GreedyAgent.choose(&state).unwrap().column(), 3);
+    }
+
+    #[test]
+    fn prefers_the_center() {
+        let state = Connect4State::from_moves("444444").unwrap();
+        let column = GreedyAgent.choose(&state).unwrap().column();
+        assert!(column == 2 || column == 4);
+    }
+}
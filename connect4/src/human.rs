@@ -0,0 +1,112 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{validate_action, ActionError, Agent, Connect4Action, Connect4State};
+
+/// Asks a person for moves. Columns are typed 1-indexed, like the move notation, with a `-` in
+/// front to pop out with the Pop Out rules. Running out of input resigns the game.
+pub struct HumanAgent<R, W> {
+    input: R,
+    output: W,
+}
+
+impl HumanAgent<io::StdinLock<'static>, io::Stdout> {
+    pub fn stdin() -> Self {
+        Self::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> HumanAgent<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    fn prompt(&mut self, state: &Connect4State) -> io::Result<Option<Connect4Action>> {
+        write!(self.output, "{}", state)?;
+        let cols = state.config.cols;
+        loop {
+            write!(self.output, "Column (1-{}): ", cols)?;
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                writeln!(self.output)?;
+                return Ok(None);
+            }
+            let line = line.trim();
+            let (pop_out, column) = match line.strip_prefix('-') {
+                Some(column) if state.config.pop_out => (true, column),
+                _ => (false, line),
+            };
+            let column = match column.parse::<usize>() {
+                Ok(column) if (1..=cols).contains(&column) => column - 1,
+                _ => {
+                    writeln!(self.output, "Pick a column between 1 and {}.", cols)?;
+                    continue;
+                }
+            };
+            let action = if pop_out {
+                Connect4Action::PopOut { column }
+            } else {
+                Connect4Action::Drop { column }
+            };
+            // The errors count columns from 0, people count them from 1.
+            match validate_action(state, &action) {
+                Ok(()) => return Ok(Some(action)),
+                Err(ActionError::FullColumn(_)) => {
+                    writeln!(self.output, "Column {} is full.", column + 1)?
+                }
+                Err(ActionError::NotYourDisc(_)) => writeln!(
+                    self.output,
+                    "The bottom of column {} isn't yours.",
+                    column + 1
+                )?,
+                Err(ActionError::UnknownColumn(_)) => {
+                    writeln!(self.output, "Pick a column between 1 and {}.", cols)?
+                }
+                Err(error) => writeln!(self.output, "{}", error)?,
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Agent for HumanAgent<R, W> {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        // If the terminal goes away there's nobody left to play.
+        self.prompt(state).unwrap_or(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reprompts_until_the_move_is_legal() {
+        let state = Connect4State::from_moves("111111").unwrap();
+        let input = "hello\n9\n1\n2\n".as_bytes();
+        let mut output = vec![];
+        let action = HumanAgent::new(input, &mut output).choose(&state);
+        assert_eq!(action.unwrap().column(), 1);
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("Pick a column between 1 and 7.").count(), 2);
+        assert!(output.contains("Column 1 is full."));
+    }
+
+    #[test]
+    fn says_why_a_pop_out_is_illegal() {
+        let config = crate::BoardConfig::default().with_pop_out();
+        let state = Connect4State::from_moves_with_config(config, "12").unwrap();
+        let mut output = vec![];
+        let action = HumanAgent::new("-2\n-1\n".as_bytes(), &mut output).choose(&state);
+        assert_eq!(action, Some(Connect4Action::PopOut { column: 0 }));
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("The bottom of column 2 isn't yours."));
+    }
+
+    #[test]
+    fn resigns_on_eof() {
+        let state = Connect4State::default();
+        let action = HumanAgent::new("".as_bytes(), vec![]).choose(&state);
+        assert!(action.is_none());
+    }
+}
@@ -0,0 +1,1748 @@
+//! Connect 4 rules and agents to play it. `Connect4State` holds a game, `apply_action` plays
+//! moves and `check_state` says if it's over. `play` runs a game between two `Agent`s.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use thiserror::Error;
+
+pub mod bitboard;
+pub mod elo;
+pub mod eval;
+#[cfg(feature = "exdraw")]
+pub mod excalidraw;
+pub mod features;
+pub mod greedy;
+pub mod human;
+pub mod mcts;
+pub mod minimax;
+pub mod opening;
+#[cfg(feature = "serde")]
+pub mod record;
+pub mod replay;
+pub mod self_play;
+pub mod solver;
+pub mod threats;
+pub mod tournament;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zobrist;
+
+pub use greedy::GreedyAgent;
+pub use human::HumanAgent;
+pub use mcts::MctsAgent;
+pub use minimax::MinimaxAgent;
+
+// `std::time::Instant` panics in the browser, the one from `web-time` works there too.
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+const ROWS: usize = 6;
+const COLS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Connect4Action {
+    /// Drop a disc on top of a column.
+    Drop { column: usize },
+    /// Pop Out variant only. Take your own disc off the bottom of a column, the rest of the
+    /// column falls down one row.
+    PopOut { column: usize },
+}
+
+impl Connect4Action {
+    pub fn column(&self) -> usize {
+        match *self {
+            Connect4Action::Drop { column } | Connect4Action::PopOut { column } => column,
+        }
+    }
+}
+
+/// Dimensions of the board, the number of discs in a row needed to win and which rules are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "ConfigFields")
+)]
+pub struct BoardConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub connect: usize,
+    /// Allow `Connect4Action::PopOut` moves.
+    pub pop_out: bool,
+    /// How many players take turns, 2 unless it's set with `with_players`. Players are numbered
+    /// from 0 and move in that order.
+    pub players: usize,
+}
+
+// The fields `BoardConfig` is read from, checked like the constructors check them.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ConfigFields {
+    rows: usize,
+    cols: usize,
+    connect: usize,
+    pop_out: bool,
+    #[serde(default = "two_players")]
+    players: usize,
+}
+
+// Configs saved before there could be more players were all for two.
+#[cfg(feature = "serde")]
+fn two_players() -> usize {
+    2
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ConfigFields> for BoardConfig {
+    type Error = BoardConfigError;
+
+    fn try_from(fields: ConfigFields) -> Result<Self, Self::Error> {
+        let config = BoardConfig::new(fields.rows, fields.cols, fields.connect)?
+            .with_players(fields.players)?;
+        Ok(if fields.pop_out {
+            config.with_pop_out()
+        } else {
+            config
+        })
+    }
+}
+
+/// The widest board `BoardConfig::new` makes. Moves are written with one base 36 digit per
+/// column, see `Connect4State::from_moves`.
+pub const MAX_COLS: usize = 35;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BoardConfigError {
+    #[error("The board needs at least one row and one column. Got `{0}x{1}`.")]
+    EmptyBoard(usize, usize),
+    #[error("Need to connect at least one disc.")]
+    NoConnect,
+    #[error("A line of `{connect}` doesn't fit on a `{rows}x{cols}` board, every game would tie.")]
+    ConnectTooLong {
+        rows: usize,
+        cols: usize,
+        connect: usize,
+    },
+    #[error("A game needs at least two players. Got `{0}`.")]
+    TooFewPlayers(usize),
+    #[error("Boards can be at most {MAX_COLS} columns wide. Got `{0}`.")]
+    TooWide(usize),
+}
+
+/// Why a state read from JSON can't be played on.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum StateError {
+    #[error("A `{rows}x{cols}` board has {} cells. Got `{got}`.", rows * cols)]
+    WrongBoardSize {
+        rows: usize,
+        cols: usize,
+        got: usize,
+    },
+    #[error("There's no player `{player}` in a game for {players}.")]
+    UnknownPlayer { player: usize, players: usize },
+    #[error("The history has a move in column `{0}`, which isn't on the board.")]
+    UnknownColumn(usize),
+}
+
+impl BoardConfig {
+    /// Checks that the board isn't empty or wider than `MAX_COLS` and that a line of `connect`
+    /// fits on it, at least horizontally or vertically.
+    pub fn new(rows: usize, cols: usize, connect: usize) -> Result<Self, BoardConfigError> {
+        if rows == 0 || cols == 0 {
+            return Err(BoardConfigError::EmptyBoard(rows, cols));
+        }
+        if cols > MAX_COLS {
+            return Err(BoardConfigError::TooWide(cols));
+        }
+        if connect == 0 {
+            return Err(BoardConfigError::NoConnect);
+        }
+        if connect > rows && connect > cols {
+            return Err(BoardConfigError::ConnectTooLong {
+                rows,
+                cols,
+                connect,
+            });
+        }
+        Ok(Self {
+            rows,
+            cols,
+            connect,
+            pop_out: false,
+            players: 2,
+        })
+    }
+
+    /// The same board played with the Pop Out rules.
+    pub fn with_pop_out(self) -> Self {
+        Self {
+            pop_out: true,
+            ..self
+        }
+    }
+
+    /// The same board shared by `players` players. Games with more than two are free for alls,
+    /// whoever connects first wins.
+    pub fn with_players(self, players: usize) -> Result<Self, BoardConfigError> {
+        if players < 2 {
+            return Err(BoardConfigError::TooFewPlayers(players));
+        }
+        Ok(Self { players, ..self })
+    }
+
+    /// The player whose turn is after `player`'s.
+    pub fn player_after(&self, player: usize) -> usize {
+        (player + 1) % self.players
+    }
+
+    /// The player whose turn is before `player`'s.
+    pub fn player_before(&self, player: usize) -> usize {
+        (player + self.players - 1) % self.players
+    }
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self {
+            rows: ROWS,
+            cols: COLS,
+            connect: 4,
+            pop_out: false,
+            players: 2,
+        }
+    }
+}
+
+/// States are equal and hash the same when they're the same position: the same board, discs and
+/// player to move. How the game got there isn't compared, so transpositions are equal even
+/// though their `history` differs. Compare `history` as well to check it's the same game.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "StateFields")
+)]
+pub struct Connect4State {
+    pub config: BoardConfig,
+    pub board: Vec<Option<usize>>,
+    pub next_player: usize,
+    /// Moves played so far, in order. Kept up to date by `apply_action` and `undo_action`.
+    pub history: Vec<Connect4Action>,
+    /// How many discs are on the board, so checking for a full board doesn't have to look at it.
+    /// Kept up to date by `apply_action` and `undo_action`. Code that sets `board` directly has
+    /// to call `recount` after. It's left out of the JSON and counted again when reading it back,
+    /// like `heights`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    pub discs_placed: usize,
+    /// How many discs are in each column, which is also the row the next drop lands in. Kept up
+    /// to date the same way as `discs_placed`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    pub heights: Vec<usize>,
+}
+
+// The fields `Connect4State` is read from, everything but the counts.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct StateFields {
+    config: BoardConfig,
+    board: Vec<Option<usize>>,
+    next_player: usize,
+    history: Vec<Connect4Action>,
+}
+
+// Checks what the rest of the crate takes for granted, so a bad state is an error here instead of
+// a panic later.
+#[cfg(feature = "serde")]
+impl TryFrom<StateFields> for Connect4State {
+    type Error = StateError;
+
+    fn try_from(fields: StateFields) -> Result<Self, Self::Error> {
+        let BoardConfig {
+            rows,
+            cols,
+            players,
+            ..
+        } = fields.config;
+        if fields.board.len() != rows * cols {
+            return Err(StateError::WrongBoardSize {
+                rows,
+                cols,
+                got: fields.board.len(),
+            });
+        }
+        let owners = fields.board.iter().flatten();
+        if let Some(&player) = owners
+            .chain([&fields.next_player])
+            .find(|&&player| player >= players)
+        {
+            return Err(StateError::UnknownPlayer { player, players });
+        }
+        if let Some(action) = fields.history.iter().find(|action| action.column() >= cols) {
+            return Err(StateError::UnknownColumn(action.column()));
+        }
+        let mut state = Self {
+            config: fields.config,
+            board: fields.board,
+            next_player: fields.next_player,
+            history: fields.history,
+            discs_placed: 0,
+            heights: vec![],
+        };
+        state.recount();
+        Ok(state)
+    }
+}
+
+impl PartialEq for Connect4State {
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config
+            && self.board == other.board
+            && self.next_player == other.next_player
+    }
+}
+
+impl Eq for Connect4State {}
+
+impl Hash for Connect4State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.config.hash(state);
+        self.board.hash(state);
+        self.next_player.hash(state);
+    }
+}
+
+impl Connect4State {
+    pub fn new(config: BoardConfig) -> Self {
+        Self {
+            config,
+            board: vec![None; config.rows * config.cols],
+            next_player: 0,
+            history: vec![],
+            discs_placed: 0,
+            heights: vec![0; config.cols],
+        }
+    }
+
+    /// An empty board with `player` to move first.
+    pub fn with_first_player(config: BoardConfig, player: usize) -> Self {
+        Self {
+            next_player: player,
+            ..Self::new(config)
+        }
+    }
+
+    /// The player that moved first, or moves first if nobody has yet.
+    pub fn first_player(&self) -> usize {
+        let players = self.config.players;
+        (self.next_player + players - self.history.len() % players) % players
+    }
+
+    /// Sets `discs_placed` and `heights` from the board, for after changing `board` by hand.
+    pub fn recount(&mut self) {
+        let rows = self.config.rows;
+        self.discs_placed = self.board.iter().flatten().count();
+        self.heights = self
+            .board
+            .chunks(rows)
+            .map(|column| {
+                column
+                    .iter()
+                    .rposition(Option::is_some)
+                    .map_or(0, |top| top + 1)
+            })
+            .collect();
+    }
+
+    /// No room left to drop a disc anywhere.
+    pub fn is_full(&self) -> bool {
+        self.discs_placed == self.board.len()
+    }
+
+    /// The board is stored column major, row 0 is the bottom of the column.
+    pub fn cell(&self, col: usize, row: usize) -> Option<usize> {
+        self.board[col * self.config.rows + row]
+    }
+
+    /// Replays a game written as the 1-indexed columns played, e.g. `"4455667"`, on the standard
+    /// board with player 0 moving first. Columns past 9 continue with letters, `a` is column 10.
+    pub fn from_moves(moves: &str) -> Result<Connect4State, ActionError> {
+        Self::from_moves_with_config(BoardConfig::default(), moves)
+    }
+
+    /// Like `from_moves` on any board. A `-` before a column pops out of it instead of dropping.
+    pub fn from_moves_with_config(
+        config: BoardConfig,
+        moves: &str,
+    ) -> Result<Connect4State, ActionError> {
+        let mut state = Connect4State::new(config);
+        for action in parse_moves(moves)? {
+            apply_action(&mut state, &action)?;
+        }
+        Ok(state)
+    }
+
+    /// The inverse of `from_moves`.
+    pub fn to_moves(&self) -> String {
+        let mut moves = String::new();
+        for action in &self.history {
+            if let Connect4Action::PopOut { .. } = action {
+                moves.push('-');
+            }
+            moves.push(
+                char::from_digit(action.column() as u32 + 1, 36)
+                    .expect("boards are at most MAX_COLS wide"),
+            );
+        }
+        moves
+    }
+}
+
+/// Reads moves written like `Connect4State::from_moves` without playing them, so they aren't
+/// checked against a board.
+pub fn parse_moves(moves: &str) -> Result<Vec<Connect4Action>, ActionError> {
+    let mut actions = vec![];
+    let mut pop_out = false;
+    for c in moves.chars() {
+        if c == '-' && !pop_out {
+            pop_out = true;
+            continue;
+        }
+        let column = match c.to_digit(36) {
+            Some(digit) if digit > 0 => digit as usize - 1,
+            _ => return Err(ActionError::UnknownMove(c)),
+        };
+        actions.push(if pop_out {
+            Connect4Action::PopOut { column }
+        } else {
+            Connect4Action::Drop { column }
+        });
+        pop_out = false;
+    }
+    Ok(actions)
+}
+
+impl Default for Connect4State {
+    fn default() -> Self {
+        Self::new(BoardConfig::default())
+    }
+}
+
+/// Draws the board with a header of 1-indexed column numbers and row 0 at the bottom. Player 0's
+/// discs are `X`, player 1's are `O` and empty cells are `.`. Any more players' discs are their
+/// player number.
+impl fmt::Display for Connect4State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = (0..self.config.cols)
+            .map(|col| {
+                char::from_digit(col as u32 + 1, 36)
+                    .unwrap_or('?')
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", header.join(" "))?;
+        for row in (0..self.config.rows).rev() {
+            let cells = (0..self.config.cols)
+                .map(|col| match self.cell(col, row) {
+                    Some(0) => "X".to_string(),
+                    Some(1) => "O".to_string(),
+                    Some(player) => player.to_string(),
+                    None => ".".to_string(),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Connect4Result {
+    /// `line` holds the `(column, row)` of each disc in the winning line. It's empty if the other
+    /// player resigned.
+    Winner {
+        player: usize,
+        line: Vec<(usize, usize)>,
+    },
+    Tie,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Connect4Check {
+    InProgress,
+    Over(Connect4Result),
+}
+
+// Checks the `connect` cells starting at (col, row) and stepping by (d_col, d_row) and returns a
+// win if one player owns all of them, only if that's `only` when it's set. The caller makes sure
+// the whole line is on the board.
+fn line_winner(
+    state: &Connect4State,
+    col: usize,
+    row: usize,
+    d_col: isize,
+    d_row: isize,
+    only: Option<usize>,
+) -> Option<Connect4Result> {
+    let player = state.cell(col, row)?;
+    if only.is_some_and(|only| only != player) {
+        return None;
+    }
+    let cell_at = |i: usize| {
+        (
+            (col as isize + d_col * i as isize) as usize,
+            (row as isize + d_row * i as isize) as usize,
+        )
+    };
+    for i in 1..state.config.connect {
+        let (c, r) = cell_at(i);
+        if state.cell(c, r) != Some(player) {
+            return None;
+        }
+    }
+    Some(Connect4Result::Winner {
+        player,
+        line: (0..state.config.connect).map(cell_at).collect(),
+    })
+}
+
+pub fn check_state(state: &Connect4State) -> Connect4Check {
+    use Connect4Check::*;
+    match find_line(state, None) {
+        Some(winner) => Over(winner),
+        None if state.is_full() => Over(Connect4Result::Tie),
+        None => InProgress,
+    }
+}
+
+// Scans the whole board for a winning line, only `only`'s lines if it's set.
+fn find_line(state: &Connect4State, only: Option<usize>) -> Option<Connect4Result> {
+    let BoardConfig {
+        rows,
+        cols,
+        connect,
+        ..
+    } = state.config;
+    // Number of starting positions for a line along each axis. Lines that don't fit on the board
+    // have none, so the only way for the game to end is a tie.
+    let connect = connect.max(1);
+    let col_starts = (cols + 1).saturating_sub(connect);
+    let row_starts = (rows + 1).saturating_sub(connect);
+
+    // Check vertical wins
+    for col in 0..cols {
+        for row in 0..row_starts {
+            if let Some(winner) = line_winner(state, col, row, 0, 1, only) {
+                return Some(winner);
+            }
+        }
+    }
+
+    // Check horizontal wins
+    for row in 0..rows {
+        for col in 0..col_starts {
+            if let Some(winner) = line_winner(state, col, row, 1, 0, only) {
+                return Some(winner);
+            }
+        }
+    }
+
+    // Check diagonal up wins
+    for col in 0..col_starts {
+        for row in 0..row_starts {
+            if let Some(winner) = line_winner(state, col, row, 1, 1, only) {
+                return Some(winner);
+            }
+        }
+    }
+
+    // Check diagonal down wins
+    for col in 0..col_starts {
+        for row in rows - row_starts..rows {
+            if let Some(winner) = line_winner(state, col, row, 1, -1, only) {
+                return Some(winner);
+            }
+        }
+    }
+
+    None
+}
+
+/// Like `check_state` but only looks at lines through (col, row), where `player` just dropped a
+/// disc. Any win on the board has to go through the last disc played so this finds the same result
+/// as `check_state` as long as the game wasn't already over before the move.
+pub fn check_last_move(
+    state: &Connect4State,
+    col: usize,
+    row: usize,
+    player: usize,
+) -> Connect4Check {
+    use Connect4Check::*;
+    let BoardConfig {
+        rows,
+        cols,
+        connect,
+        ..
+    } = state.config;
+    let connect = connect.max(1);
+    let owned = |c: isize, r: isize| {
+        c >= 0
+            && r >= 0
+            && (c as usize) < cols
+            && (r as usize) < rows
+            && state.cell(c as usize, r as usize) == Some(player)
+    };
+
+    // Vertical, horizontal, diagonal up and diagonal down.
+    for (d_col, d_row) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+        // Walk back to the start of the run of `player`'s discs through (col, row) and then
+        // forward to its end.
+        let (mut start, mut len) = (0, 1);
+        while owned(
+            col as isize + d_col * (start - 1),
+            row as isize + d_row * (start - 1),
+        ) {
+            start -= 1;
+            len += 1;
+        }
+        while owned(
+            col as isize + d_col * (start + len),
+            row as isize + d_row * (start + len),
+        ) {
+            len += 1;
+        }
+        if len >= connect as isize {
+            let line = (start..start + connect as isize)
+                .map(|i| {
+                    (
+                        (col as isize + d_col * i) as usize,
+                        (row as isize + d_row * i) as usize,
+                    )
+                })
+                .collect();
+            return Over(Connect4Result::Winner { player, line });
+        }
+    }
+
+    if state.is_full() {
+        Over(Connect4Result::Tie)
+    } else {
+        InProgress
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ActionError {
+    #[error("Column `{0}` is off the board.")]
+    UnknownColumn(usize),
+    #[error("Column `{0}` is full.")]
+    FullColumn(usize),
+    #[error("`{0}` is not a column.")]
+    UnknownMove(char),
+    #[error("Column `{0}` is not the last move played.")]
+    NotLastMove(usize),
+    #[error("Pop outs are only allowed with the Pop Out rules.")]
+    PopOutNotAllowed,
+    #[error("The bottom disc of column `{0}` isn't yours.")]
+    NotYourDisc(usize),
+}
+
+/// Checks `action` can be played in `state`, and if it can't says why. `apply_action` fails with
+/// the same error.
+pub fn validate_action(state: &Connect4State, action: &Connect4Action) -> Result<(), ActionError> {
+    use ActionError::*;
+    let column = action.column();
+    if column >= state.config.cols {
+        return Err(UnknownColumn(column));
+    }
+    match action {
+        Connect4Action::Drop { .. } if state.heights[column] >= state.config.rows => {
+            Err(FullColumn(column))
+        }
+        Connect4Action::PopOut { .. } if !state.config.pop_out => Err(PopOutNotAllowed),
+        Connect4Action::PopOut { .. } if state.cell(column, 0) != Some(state.next_player) => {
+            Err(NotYourDisc(column))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `validate_action` without the reason.
+pub fn check_action(state: &Connect4State, action: &Connect4Action) -> bool {
+    validate_action(state, action).is_ok()
+}
+
+/// Columns that aren't full yet.
+pub fn legal_moves(state: &Connect4State) -> Vec<usize> {
+    (0..state.config.cols)
+        .filter(|&column| check_action(state, &Connect4Action::Drop { column }))
+        .collect()
+}
+
+/// Every column, the middle ones first and then working out towards the edges, left before right.
+/// Central columns are part of more lines, so searches that try them first find good moves and
+/// cut off sooner. The standard board gives `3, 2, 4, 1, 5, 0, 6`.
+pub fn ordered_columns(cols: usize) -> impl Iterator<Item = usize> {
+    let mut columns: Vec<_> = (0..cols).collect();
+    // Twice the distance from the middle, so it stays a whole number on even width boards.
+    columns.sort_by_key(|&column| (2 * column).abs_diff(cols - 1));
+    columns.into_iter()
+}
+
+/// The position flipped left to right, with the history flipped to match. The rules are the same
+/// both ways round, so a position and its mirror are worth the same and searches only need to
+/// look at one of them.
+pub fn mirror(state: &Connect4State) -> Connect4State {
+    let BoardConfig { rows, cols, .. } = state.config;
+    let mut mirrored = state.clone();
+    for col in 0..cols {
+        let flipped = cols - 1 - col;
+        mirrored.board[flipped * rows..(flipped + 1) * rows]
+            .copy_from_slice(&state.board[col * rows..(col + 1) * rows]);
+    }
+    mirrored.heights.reverse();
+    for action in &mut mirrored.history {
+        *action = match *action {
+            Connect4Action::Drop { column } => Connect4Action::Drop {
+                column: cols - 1 - column,
+            },
+            Connect4Action::PopOut { column } => Connect4Action::PopOut {
+                column: cols - 1 - column,
+            },
+        };
+    }
+    mirrored
+}
+
+/// Every legal action, drops first and then pop outs if the rules allow them.
+pub fn legal_actions(state: &Connect4State) -> Vec<Connect4Action> {
+    let drops = legal_moves(state)
+        .into_iter()
+        .map(|column| Connect4Action::Drop { column });
+    let pops = (0..state.config.cols)
+        .map(|column| Connect4Action::PopOut { column })
+        .filter(|action| check_action(state, action));
+    drops.chain(pops).collect()
+}
+
+pub fn apply_action(
+    state: &mut Connect4State,
+    action: &Connect4Action,
+) -> Result<Connect4Check, ActionError> {
+    validate_action(state, action)?;
+    let rows = state.config.rows;
+    let column = action.column();
+    let player = state.next_player;
+    match action {
+        Connect4Action::Drop { .. } => {
+            let row = state.heights[column];
+            state.board[column * rows + row] = Some(player);
+            state.heights[column] += 1;
+            state.discs_placed += 1;
+            state.next_player = state.config.player_after(state.next_player);
+            state.history.push(*action);
+            Ok(check_last_move(state, column, row, player))
+        }
+        Connect4Action::PopOut { .. } => {
+            let cells = &mut state.board[column * rows..(column + 1) * rows];
+            cells.rotate_left(1);
+            cells[rows - 1] = None;
+            state.heights[column] -= 1;
+            state.discs_placed -= 1;
+            state.next_player = state.config.player_after(state.next_player);
+            state.history.push(*action);
+            // Every disc in the column moved so lines can appear anywhere along it, for both
+            // players. If the pop makes a line for the player who popped they win, even if it
+            // makes one for the other player too.
+            let check = match find_line(state, Some(player)).or_else(|| find_line(state, None)) {
+                Some(winner) => Connect4Check::Over(winner),
+                None if state.is_full() => Connect4Check::Over(Connect4Result::Tie),
+                None => Connect4Check::InProgress,
+            };
+            Ok(check)
+        }
+    }
+}
+
+/// Takes back `action`, it has to be the most recent move. Search code should undo moves in the
+/// reverse order it applied them.
+pub fn undo_action(state: &mut Connect4State, action: &Connect4Action) -> Result<(), ActionError> {
+    use ActionError::*;
+    let rows = state.config.rows;
+    let column = action.column();
+    if column >= state.config.cols {
+        return Err(UnknownColumn(column));
+    }
+    if state.history.last() != Some(action) {
+        return Err(NotLastMove(column));
+    }
+    // The player that made the move.
+    let player = state.config.player_before(state.next_player);
+    let cells = &mut state.board[column * rows..(column + 1) * rows];
+    match action {
+        Connect4Action::Drop { .. } => {
+            let Some(top) = state.heights[column].checked_sub(1) else {
+                return Err(NotLastMove(column));
+            };
+            cells[top] = None;
+            state.heights[column] = top;
+            state.discs_placed -= 1;
+        }
+        Connect4Action::PopOut { .. } => {
+            // Put the popped disc back under the column.
+            cells.rotate_right(1);
+            cells[0] = Some(player);
+            state.heights[column] += 1;
+            state.discs_placed += 1;
+        }
+    }
+    state.next_player = player;
+    state.history.pop();
+    Ok(())
+}
+
+/// Something that can pick moves. Agents take `&mut self` so they can keep state (random number
+/// generators, search trees, ...) between turns.
+pub trait Agent {
+    /// Returns `None` to resign.
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action>;
+
+    /// What to call the agent in tournament results and game records. The type's name without
+    /// its module or generic parameters by default, like `MctsAgent`.
+    fn name(&self) -> &str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Gives the agent another name, for telling apart agents of the same type set up
+    /// differently.
+    fn named(self, name: impl Into<String>) -> Named<Self>
+    where
+        Self: Sized,
+    {
+        Named {
+            agent: self,
+            name: name.into(),
+        }
+    }
+}
+
+/// Boxed agents play like the agent in the box and keep its name.
+impl<A: Agent + ?Sized> Agent for Box<A> {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        (**self).choose(state)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/// An agent going by a name of its own, see `Agent::named`.
+pub struct Named<A> {
+    pub agent: A,
+    pub name: String,
+}
+
+impl<A: Agent> Agent for Named<A> {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        self.agent.choose(state)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// How much searching agents like `MctsAgent` and `MinimaxAgent` have done, added up over every
+/// move they've picked.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SearchStats {
+    /// Simulations for MCTS, positions searched for minimax.
+    pub nodes: u64,
+    /// Time spent picking moves.
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    /// 0 before any time has been spent, like when every move came from an opening book.
+    pub fn nodes_per_second(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.;
+        }
+        self.nodes as f64 / self.elapsed.as_secs_f64()
+    }
+
+    // Adds one move's search.
+    pub(crate) fn record(&mut self, nodes: u64, elapsed: Duration) {
+        self.nodes += nodes;
+        self.elapsed += elapsed;
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} nodes in {:.2?}, {:.0} nodes/s",
+            self.nodes,
+            self.elapsed,
+            self.nodes_per_second()
+        )
+    }
+}
+
+/// Watches a game being played with `play_observed`, to draw it or record it as it goes.
+pub trait GameObserver {
+    /// Called after every move with the state after `action` was applied.
+    fn on_move(&mut self, state: &Connect4State, action: &Connect4Action);
+}
+
+/// Doesn't watch, what `play` uses.
+impl GameObserver for () {
+    fn on_move(&mut self, _state: &Connect4State, _action: &Connect4Action) {}
+}
+
+/// How a game played with `play` went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOutcome {
+    pub result: Connect4Result,
+    /// Every move in the game, including the ones made before `play` picked it up.
+    pub history: Vec<Connect4Action>,
+    /// The player that lost on time with `play_with_time_limit`.
+    pub timed_out: Option<usize>,
+}
+
+/// Plays the game in `state` to the end. Player 0 is `blue_agent` and player 1 is `red_agent`,
+/// and whoever's turn it is in `state` goes first, so passing a game in progress resumes it.
+/// Agents can be anything that implements `Agent`, including `dyn Agent`.
+///
+/// Returns an error if an agent picks an illegal move.
+pub fn play<B, R>(
+    state: &mut Connect4State,
+    blue_agent: &mut B,
+    red_agent: &mut R,
+) -> Result<GameOutcome, ActionError>
+where
+    B: Agent + ?Sized,
+    R: Agent + ?Sized,
+{
+    play_observed(state, blue_agent, red_agent, &mut ())
+}
+
+/// `play` that tells `observer` about every move.
+pub fn play_observed<B, R, O>(
+    state: &mut Connect4State,
+    blue_agent: &mut B,
+    red_agent: &mut R,
+    observer: &mut O,
+) -> Result<GameOutcome, ActionError>
+where
+    B: Agent + ?Sized,
+    R: Agent + ?Sized,
+    O: GameObserver + ?Sized,
+{
+    play_turns(state, observer, |state| {
+        if state.next_player == 0 {
+            blue_agent.choose(state)
+        } else {
+            red_agent.choose(state)
+        }
+    })
+}
+
+/// `play` for any number of players, player `i` is `agents[i]`. There has to be an agent for
+/// every player in the state's config. When a player resigns the next player in turn wins.
+pub fn play_players(
+    state: &mut Connect4State,
+    agents: &mut [&mut dyn Agent],
+) -> Result<GameOutcome, ActionError> {
+    assert_eq!(
+        agents.len(),
+        state.config.players,
+        "need one agent per player"
+    );
+    play_turns(state, &mut (), |state| {
+        agents[state.next_player].choose(state)
+    })
+}
+
+// Asks `choose` for moves for whoever's turn it is until the game ends.
+fn play_turns<O: GameObserver + ?Sized>(
+    state: &mut Connect4State,
+    observer: &mut O,
+    mut choose: impl FnMut(&Connect4State) -> Option<Connect4Action>,
+) -> Result<GameOutcome, ActionError> {
+    let result = loop {
+        let Some(action) = choose(state) else {
+            // The next player wins by resignation, there's no line to show.
+            break Connect4Result::Winner {
+                player: state.config.player_after(state.next_player),
+                line: vec![],
+            };
+        };
+        let check = apply_action(state, &action)?;
+        observer.on_move(state, &action);
+        if let Connect4Check::Over(result) = check {
+            break result;
+        }
+    };
+    Ok(GameOutcome {
+        result,
+        history: state.history.clone(),
+        timed_out: None,
+    })
+}
+
+/// `play` where each agent gets `limit` to pick every move. An agent that takes longer, or
+/// panics, loses the game like it resigned and `timed_out` says which player it was.
+///
+/// Agents run on their own threads so the game can go on without them. Rust can't stop a thread
+/// from outside, so an agent that's given up on keeps running until its `choose` returns and only
+/// then sees it isn't wanted anymore. Agents that might think for a long time should watch a clock
+/// of their own, like `MctsAgent` with a time budget, so the threads finish soon after the game.
+pub fn play_with_time_limit<B, R>(
+    state: &mut Connect4State,
+    blue_agent: B,
+    red_agent: R,
+    limit: Duration,
+) -> Result<GameOutcome, ActionError>
+where
+    B: Agent + Send + 'static,
+    R: Agent + Send + 'static,
+{
+    let mut blue = TimedAgent::spawn(blue_agent, limit);
+    let mut red = TimedAgent::spawn(red_agent, limit);
+    let mut outcome = play(state, &mut blue, &mut red)?;
+    outcome.timed_out = [&blue, &red].iter().position(|agent| agent.timed_out);
+    Ok(outcome)
+}
+
+// Asks an agent running on another thread for moves and resigns for it if it doesn't answer in
+// time. Dropping this closes the channel and the thread exits after its current move.
+struct TimedAgent {
+    name: String,
+    states: Sender<Connect4State>,
+    actions: Receiver<Option<Connect4Action>>,
+    limit: Duration,
+    timed_out: bool,
+}
+
+impl TimedAgent {
+    fn spawn<A: Agent + Send + 'static>(mut agent: A, limit: Duration) -> Self {
+        let name = agent.name().to_string();
+        let (states, state_receiver) = mpsc::channel::<Connect4State>();
+        let (action_sender, actions) = mpsc::channel();
+        thread::spawn(move || {
+            for state in state_receiver {
+                if action_sender.send(agent.choose(&state)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            name,
+            states,
+            actions,
+            limit,
+            timed_out: false,
+        }
+    }
+}
+
+impl Agent for TimedAgent {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        let answer = match self.states.send(state.clone()) {
+            Ok(()) => self.actions.recv_timeout(self.limit).ok(),
+            Err(_) => None,
+        };
+        // `None` here means the agent didn't answer, not that it resigned.
+        if answer.is_none() {
+            self.timed_out = true;
+        }
+        answer.flatten()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Same seed, same moves. Useful for reproducing games.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for RandomAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for RandomAgent {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        legal_actions(state).choose(&mut self.rng).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Thinks for longer than the time limit in the tests.
+    struct SlowAgent;
+
+    impl Agent for SlowAgent {
+        fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+            thread::sleep(Duration::from_millis(200));
+            legal_actions(state).first().copied()
+        }
+    }
+
+    #[test]
+    fn no_time_no_speed() {
+        let mut stats = SearchStats::default();
+        assert_eq!(stats.nodes_per_second(), 0.);
+        stats.record(500, Duration::from_millis(250));
+        assert_eq!(stats.nodes_per_second(), 2000.);
+        assert!(stats.to_string().ends_with(", 2000 nodes/s"));
+    }
+
+    #[test]
+    fn slow_agents_lose_on_time() {
+        let mut state = Connect4State::from_moves("4").unwrap();
+        let outcome = play_with_time_limit(
+            &mut state,
+            RandomAgent::seeded(0),
+            SlowAgent,
+            Duration::from_millis(20),
+        )
+        .unwrap();
+        assert_eq!(outcome.timed_out, Some(1));
+        assert_eq!(
+            outcome.result,
+            Connect4Result::Winner {
+                player: 0,
+                line: vec![]
+            }
+        );
+        assert_eq!(outcome.history.len(), 1);
+    }
+
+    #[test]
+    fn time_limit_doesnt_change_the_game() {
+        let mut timed = Connect4State::default();
+        let outcome = play_with_time_limit(
+            &mut timed,
+            RandomAgent::seeded(1),
+            RandomAgent::seeded(2),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        let mut untimed = Connect4State::default();
+        let expected = play(
+            &mut untimed,
+            &mut RandomAgent::seeded(1),
+            &mut RandomAgent::seeded(2),
+        )
+        .unwrap();
+        assert_eq!(outcome, expected);
+    }
+
+    #[test]
+    fn agents_are_named_after_their_type() {
+        assert_eq!(RandomAgent::seeded(0).name(), "RandomAgent");
+        assert_eq!(HumanAgent::stdin().name(), "HumanAgent");
+        let agent: Box<dyn Agent> = Box::new(MctsAgent::default());
+        assert_eq!(agent.name(), "MctsAgent");
+        assert_eq!(
+            MctsAgent::default().named("MCTS(1000)").name(),
+            "MCTS(1000)"
+        );
+        let timed = TimedAgent::spawn(GreedyAgent, Duration::from_secs(1));
+        assert_eq!(timed.name(), "GreedyAgent");
+    }
+
+    #[test]
+    fn second_player_can_start() {
+        let mut state = Connect4State::with_first_player(BoardConfig::default(), 1);
+        assert_eq!(state.first_player(), 1);
+        apply_action(&mut state, &Connect4Action::Drop { column: 3 }).unwrap();
+        assert_eq!(state.cell(3, 0), Some(1));
+        assert_eq!((state.next_player, state.first_player()), (0, 1));
+        undo_action(&mut state, &Connect4Action::Drop { column: 3 }).unwrap();
+        assert_eq!(state.next_player, 1);
+    }
+
+    #[test]
+    fn connect_five_on_a_wide_board() {
+        let mut state = Connect4State::new(BoardConfig::new(8, 8, 5).unwrap());
+        // Player 0 builds along the bottom row, player 1 stacks on top of them.
+        for col in 0..4 {
+            apply_action(&mut state, &Connect4Action::Drop { column: col }).unwrap();
+            let check = apply_action(&mut state, &Connect4Action::Drop { column: col }).unwrap();
+            assert!(matches!(check, Connect4Check::InProgress));
+        }
+        let check = apply_action(&mut state, &Connect4Action::Drop { column: 4 }).unwrap();
+        match check {
+            Connect4Check::Over(Connect4Result::Winner { player, line }) => {
+                assert_eq!(player, 0);
+                assert_eq!(line, vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+            }
+            _ => panic!("expected a win, got {:?}", check),
+        }
+    }
+
+    fn seeded_game(seed: u64) -> Vec<Connect4Action> {
+        let mut state = Connect4State::default();
+        play(
+            &mut state,
+            &mut RandomAgent::seeded(seed),
+            &mut MctsAgent::seeded(50, std::f32::consts::SQRT_2, seed),
+        )
+        .unwrap()
+        .history
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        assert_eq!(seeded_game(7), seeded_game(7));
+    }
+
+    #[test]
+    fn transpositions_are_the_same_position() {
+        let a = Connect4State::from_moves("4453").unwrap();
+        let b = Connect4State::from_moves("5344").unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a.history, b.history);
+        let positions: std::collections::HashSet<_> = [a, b].into_iter().collect();
+        assert_eq!(positions.len(), 1);
+
+        assert_ne!(
+            Connect4State::from_moves("44").unwrap(),
+            Connect4State::from_moves("4").unwrap()
+        );
+        // Same discs but the other player to move.
+        let mut other_player = Connect4State::from_moves("44").unwrap();
+        other_player.next_player = 1;
+        assert_ne!(other_player, Connect4State::from_moves("44").unwrap());
+    }
+
+    #[test]
+    fn validate_says_why() {
+        let state = Connect4State::from_moves("111111").unwrap();
+        let drop = |column| Connect4Action::Drop { column };
+        let pop = |column| Connect4Action::PopOut { column };
+        assert!(validate_action(&state, &drop(1)).is_ok());
+        assert!(matches!(
+            validate_action(&state, &drop(7)),
+            Err(ActionError::UnknownColumn(7))
+        ));
+        assert!(matches!(
+            validate_action(&state, &drop(0)),
+            Err(ActionError::FullColumn(0))
+        ));
+        assert!(matches!(
+            validate_action(&state, &pop(0)),
+            Err(ActionError::PopOutNotAllowed)
+        ));
+
+        let config = BoardConfig::default().with_pop_out();
+        let state = Connect4State::from_moves_with_config(config, "12").unwrap();
+        assert!(validate_action(&state, &pop(0)).is_ok());
+        assert!(matches!(
+            validate_action(&state, &pop(1)),
+            Err(ActionError::NotYourDisc(1))
+        ));
+        assert!(!check_action(&state, &pop(2)));
+    }
+
+    #[test]
+    fn three_players_take_turns() {
+        let config = BoardConfig::new(6, 7, 3).unwrap().with_players(3).unwrap();
+        // Player 2 builds along the bottom row from column 3 (1-indexed) while the others play
+        // on the edges.
+        let mut state = Connect4State::from_moves_with_config(config, "12367412").unwrap();
+        assert_eq!(state.next_player, 2);
+        assert_eq!(state.first_player(), 0);
+        assert_eq!(state.cell(2, 0), Some(2));
+        assert_eq!(state.to_string().lines().nth(6), Some("X O 2 2 . X O"));
+        let check = apply_action(&mut state, &Connect4Action::Drop { column: 4 }).unwrap();
+        assert_eq!(
+            check,
+            Connect4Check::Over(Connect4Result::Winner {
+                player: 2,
+                line: vec![(2, 0), (3, 0), (4, 0)]
+            })
+        );
+        undo_action(&mut state, &Connect4Action::Drop { column: 4 }).unwrap();
+        assert_eq!(state.next_player, 2);
+
+        let mut state = Connect4State::with_first_player(config, 2);
+        apply_action(&mut state, &Connect4Action::Drop { column: 0 }).unwrap();
+        assert_eq!((state.next_player, state.first_player()), (0, 2));
+
+        assert_eq!(
+            config.with_players(1),
+            Err(BoardConfigError::TooFewPlayers(1))
+        );
+    }
+
+    #[test]
+    fn plays_with_three_agents() {
+        let config = BoardConfig::default().with_players(3).unwrap();
+        for seed in 0..10 {
+            let mut state = Connect4State::new(config);
+            let (mut a, mut b, mut c) = (
+                RandomAgent::seeded(seed),
+                GreedyAgent,
+                MctsAgent::seeded(50, std::f32::consts::SQRT_2, seed),
+            );
+            let outcome = play_players(&mut state, &mut [&mut a, &mut b, &mut c]).unwrap();
+            assert!(!outcome.history.is_empty());
+            if let Connect4Result::Winner { player, .. } = outcome.result {
+                assert!(player < 3);
+            }
+        }
+    }
+
+    #[test]
+    fn undo_reverses_apply() {
+        let mut state = Connect4State::default();
+        for column in [3, 3, 2, 4] {
+            apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+        }
+        let before = state.clone();
+        apply_action(&mut state, &Connect4Action::Drop { column: 3 }).unwrap();
+        undo_action(&mut state, &Connect4Action::Drop { column: 3 }).unwrap();
+        assert_eq!(state, before);
+        assert_eq!(state.to_moves(), "4435");
+
+        assert!(matches!(
+            undo_action(&mut state, &Connect4Action::Drop { column: 3 }),
+            Err(ActionError::NotLastMove(3))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut state = Connect4State::default();
+        let mut check = Connect4Check::InProgress;
+        for column in [3, 3, 4, 4, 5, 5, 6] {
+            check = apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+        }
+
+        let json = serde_json::to_string(&state).unwrap();
+        let round_tripped: Connect4State = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.config, state.config);
+        assert_eq!(round_tripped.board, state.board);
+        assert_eq!(round_tripped.next_player, state.next_player);
+        assert_eq!(round_tripped.history, state.history);
+        assert_eq!(round_tripped.discs_placed, 7);
+        assert_eq!(round_tripped.heights, state.heights);
+
+        let json = serde_json::to_string(&check).unwrap();
+        let round_tripped: Connect4Check = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&round_tripped).unwrap(), json);
+        assert!(matches!(
+            round_tripped,
+            Connect4Check::Over(Connect4Result::Winner { player: 0, .. })
+        ));
+
+        // Configs from before the player count are two player games.
+        let old = r#"{"rows":6,"cols":7,"connect":4,"pop_out":false}"#;
+        let config: BoardConfig = serde_json::from_str(old).unwrap();
+        assert_eq!(config, BoardConfig::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bad_json_is_an_error() {
+        let json = serde_json::to_value(Connect4State::default()).unwrap();
+        let with = |field: &str, value: serde_json::Value| {
+            let mut json = json.clone();
+            json[field] = value;
+            serde_json::from_value::<Connect4State>(json)
+        };
+        let config = &json["config"];
+        let mut empty = config.clone();
+        empty["rows"] = 0.into();
+        assert!(with("config", empty).is_err());
+        let mut alone = config.clone();
+        alone["players"] = 0.into();
+        assert!(with("config", alone).is_err());
+        assert!(with("board", serde_json::json!([null, 0])).is_err());
+        assert!(with("next_player", 2.into()).is_err());
+        let mut board = json["board"].clone();
+        board[0] = 5.into();
+        assert!(with("board", board).is_err());
+        let history = serde_json::json!([{"Drop": {"column": 7}}]);
+        assert!(with("history", history).is_err());
+        assert!(with("next_player", 1.into()).is_ok());
+    }
+
+    #[test]
+    fn move_notation_round_trip() {
+        let state = Connect4State::from_moves("4455667").unwrap();
+        assert_eq!(state.history[..2], [Connect4Action::Drop { column: 3 }; 2]);
+        assert!(matches!(
+            check_state(&state),
+            Connect4Check::Over(Connect4Result::Winner { player: 0, .. })
+        ));
+        assert_eq!(state.to_moves(), "4455667");
+
+        assert!(matches!(
+            Connect4State::from_moves("48"),
+            Err(ActionError::UnknownColumn(7))
+        ));
+        assert!(matches!(
+            Connect4State::from_moves("40"),
+            Err(ActionError::UnknownMove('0'))
+        ));
+        assert!(matches!(
+            Connect4State::from_moves("1111111"),
+            Err(ActionError::FullColumn(0))
+        ));
+    }
+
+    #[test]
+    fn play_result_matches_final_board() {
+        for seed in 0..50 {
+            let mut state = Connect4State::default();
+            let result = play(
+                &mut state,
+                &mut RandomAgent::seeded(seed),
+                &mut RandomAgent::seeded(seed + 1000),
+            )
+            .unwrap();
+            assert_eq!(check_state(&state), Connect4Check::Over(result.result));
+            assert_eq!(result.history, state.history);
+        }
+    }
+
+    #[test]
+    fn play_resumes_a_game() {
+        // Player 1 to move, with player 0 about to win in column 4 (1-indexed).
+        let start = Connect4State::from_moves("11223").unwrap();
+        let mut state = start.clone();
+        let blue: &mut dyn Agent = &mut RandomAgent::seeded(0);
+        let outcome = play(&mut state, blue, &mut greedy::GreedyAgent).unwrap();
+        assert_eq!(outcome.history[..5], start.history);
+        // Red blocks column 4 first.
+        assert_eq!(outcome.history[5], Connect4Action::Drop { column: 3 });
+    }
+
+    #[test]
+    fn observer_sees_every_move() {
+        struct Recorder(Vec<String>);
+        impl GameObserver for Recorder {
+            fn on_move(&mut self, state: &Connect4State, action: &Connect4Action) {
+                assert_eq!(state.history.last(), Some(action));
+                self.0.push(state.to_moves());
+            }
+        }
+
+        let mut recorder = Recorder(vec![]);
+        let mut state = Connect4State::default();
+        let outcome = play_observed(
+            &mut state,
+            &mut RandomAgent::seeded(5),
+            &mut RandomAgent::seeded(6),
+            &mut recorder,
+        )
+        .unwrap();
+        assert_eq!(recorder.0.len(), outcome.history.len());
+        assert_eq!(recorder.0.last(), Some(&state.to_moves()));
+    }
+
+    #[test]
+    fn check_last_move_matches_check_state() {
+        for seed in 0..100 {
+            let mut agent = RandomAgent::seeded(seed);
+            let mut state = Connect4State::default();
+            loop {
+                let action = agent.choose(&state).unwrap();
+                let check = apply_action(&mut state, &action).unwrap();
+                assert_eq!(check, check_state(&state));
+                if check != Connect4Check::InProgress {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_last_move_joins_two_runs() {
+        // Player 0 has discs in columns 0, 1 and 3 and then fills the gap.
+        let state = Connect4State::from_moves("1122443").unwrap();
+        assert_eq!(
+            check_last_move(&state, 2, 0, 0),
+            Connect4Check::Over(Connect4Result::Winner {
+                player: 0,
+                line: vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+            })
+        );
+    }
+
+    #[test]
+    fn legal_moves_skip_full_columns() {
+        let mut state = Connect4State::from_moves("444444").unwrap();
+        assert_eq!(legal_moves(&state), vec![0, 1, 2, 4, 5, 6]);
+        let mut agent = RandomAgent::seeded(3);
+        while check_state(&state) == Connect4Check::InProgress {
+            let moves = legal_moves(&state);
+            assert!(!moves.contains(&3));
+            assert!(moves
+                .iter()
+                .all(|&column| state.cell(column, ROWS - 1).is_none()));
+            let action = agent.choose(&state).unwrap();
+            apply_action(&mut state, &action).unwrap();
+        }
+    }
+
+    #[test]
+    fn center_columns_first() {
+        assert_eq!(
+            ordered_columns(7).collect::<Vec<_>>(),
+            [3, 2, 4, 1, 5, 0, 6]
+        );
+        assert_eq!(ordered_columns(6).collect::<Vec<_>>(), [2, 3, 1, 4, 0, 5]);
+        assert_eq!(ordered_columns(1).collect::<Vec<_>>(), [0]);
+        assert_eq!(ordered_columns(0).count(), 0);
+    }
+
+    #[test]
+    fn random_agent_finds_the_last_open_column() {
+        // Every column but the last one is full.
+        let state = Connect4State::from_moves(&"123456".repeat(6)).unwrap();
+        assert_eq!(legal_moves(&state), vec![6]);
+        for seed in 0..10 {
+            let action = RandomAgent::seeded(seed).choose(&state).unwrap();
+            assert_eq!(action.column(), 6);
+        }
+    }
+
+    fn assert_winner(state: &Connect4State, player: usize) {
+        match check_state(state) {
+            Connect4Check::Over(Connect4Result::Winner { player: p, line }) => {
+                assert_eq!(p, player);
+                assert_eq!(line.len(), 5);
+            }
+            check => panic!("expected a win, got {:?}", check),
+        }
+    }
+
+    #[test]
+    fn connect_five_in_every_direction() {
+        let config = BoardConfig::new(9, 9, 5).unwrap();
+        let play_moves = |moves: &[usize]| {
+            let mut state = Connect4State::new(config);
+            for &column in moves {
+                apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+            }
+            state
+        };
+
+        // Vertical, player 0 stacks column 0 while player 1 plays column 1.
+        assert_winner(&play_moves(&[0, 1, 0, 1, 0, 1, 0, 1, 0]), 0);
+        // Horizontal along the bottom row.
+        assert_winner(&play_moves(&[0, 0, 1, 1, 2, 2, 3, 3, 4]), 0);
+        // Four in a row isn't enough.
+        let four = play_moves(&[0, 0, 1, 1, 2, 2, 3]);
+        assert_eq!(check_state(&four), Connect4Check::InProgress);
+
+        // Diagonals, built by hand since the move order doesn't matter to check_state.
+        let mut up = Connect4State::new(config);
+        let mut down = Connect4State::new(config);
+        for i in 0..5 {
+            up.board[i * 9 + i] = Some(1);
+            down.board[(i + 2) * 9 + 6 - i] = Some(1);
+        }
+        up.recount();
+        down.recount();
+        assert_winner(&up, 1);
+        assert_winner(&down, 1);
+    }
+
+    #[test]
+    fn connect_must_fit_on_the_board() {
+        assert!(BoardConfig::new(9, 9, 5).is_ok());
+        // Only fits horizontally, still a real game.
+        assert!(BoardConfig::new(3, 9, 5).is_ok());
+        assert_eq!(
+            BoardConfig::new(4, 4, 5),
+            Err(BoardConfigError::ConnectTooLong {
+                rows: 4,
+                cols: 4,
+                connect: 5
+            })
+        );
+        assert_eq!(BoardConfig::new(6, 7, 0), Err(BoardConfigError::NoConnect));
+        assert!(BoardConfig::new(6, MAX_COLS, 4).is_ok());
+        assert_eq!(
+            BoardConfig::new(6, MAX_COLS + 1, 4),
+            Err(BoardConfigError::TooWide(36))
+        );
+        let mut wide = Connect4State::new(BoardConfig::new(6, MAX_COLS, 4).unwrap());
+        apply_action(
+            &mut wide,
+            &Connect4Action::Drop {
+                column: MAX_COLS - 1,
+            },
+        )
+        .unwrap();
+        assert_eq!(wide.to_moves(), "z");
+        assert_eq!(
+            BoardConfig::new(0, 7, 4),
+            Err(BoardConfigError::EmptyBoard(0, 7))
+        );
+
+        // A board built by hand with a line that can't fit just plays out to a tie.
+        let mut state = Connect4State::new(BoardConfig {
+            rows: 2,
+            cols: 2,
+            connect: 3,
+            pop_out: false,
+            players: 2,
+        });
+        for column in [0, 1, 1, 0] {
+            apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+        }
+        assert_eq!(
+            check_state(&state),
+            Connect4Check::Over(Connect4Result::Tie)
+        );
+    }
+
+    #[test]
+    fn counts_discs() {
+        let config = BoardConfig::default().with_pop_out();
+        let mut state = Connect4State::from_moves_with_config(config, "4453").unwrap();
+        assert_eq!(state.discs_placed, 4);
+        let pop = Connect4Action::PopOut { column: 3 };
+        apply_action(&mut state, &pop).unwrap();
+        assert_eq!(state.discs_placed, 3);
+        undo_action(&mut state, &pop).unwrap();
+        undo_action(&mut state, &Connect4Action::Drop { column: 2 }).unwrap();
+        assert_eq!(state.discs_placed, 3);
+        assert_eq!(state.heights, [0, 0, 0, 2, 1, 0, 0]);
+        let mut recounted = state.clone();
+        recounted.recount();
+        assert_eq!(
+            (recounted.discs_placed, recounted.heights),
+            (state.discs_placed, state.heights)
+        );
+
+        let mut full = Connect4State::new(BoardConfig::new(1, 2, 2).unwrap());
+        apply_action(&mut full, &Connect4Action::Drop { column: 0 }).unwrap();
+        assert!(!full.is_full());
+        apply_action(&mut full, &Connect4Action::Drop { column: 1 }).unwrap();
+        assert!(full.is_full());
+    }
+
+    #[test]
+    fn pop_out() {
+        let config = BoardConfig::default().with_pop_out();
+        // Player 0 owns the bottom of column 1 (1-indexed), player 1 the disc above it.
+        let mut state = Connect4State::from_moves_with_config(config, "1127").unwrap();
+        assert!(legal_actions(&state).contains(&Connect4Action::PopOut { column: 0 }));
+        assert!(!legal_actions(&state).contains(&Connect4Action::PopOut { column: 6 }));
+
+        let before = state.clone();
+        let pop = Connect4Action::PopOut { column: 0 };
+        assert_eq!(
+            apply_action(&mut state, &pop).unwrap(),
+            Connect4Check::InProgress
+        );
+        assert_eq!(state.cell(0, 0), Some(1));
+        assert_eq!(state.cell(0, 1), None);
+        assert_eq!(state.to_moves(), "1127-1");
+        // Player 1 can't pop player 0's disc in column 2.
+        assert!(matches!(
+            apply_action(&mut state, &Connect4Action::PopOut { column: 1 }),
+            Err(ActionError::NotYourDisc(1))
+        ));
+
+        undo_action(&mut state, &pop).unwrap();
+        assert_eq!(state.board, before.board);
+        assert_eq!(state.history, before.history);
+        assert_eq!(state.next_player, before.next_player);
+
+        // Not allowed with the standard rules.
+        let mut standard = Connect4State::from_moves("1127").unwrap();
+        assert!(matches!(
+            apply_action(&mut standard, &pop),
+            Err(ActionError::PopOutNotAllowed)
+        ));
+    }
+
+    #[test]
+    fn pop_out_wins_for_the_popper_first() {
+        let mut state = Connect4State::new(BoardConfig::default().with_pop_out());
+        // Column 0 is X O X from the bottom and columns 1 to 3 are O X. Popping the bottom X
+        // gives O four in the bottom row and X four in the row above.
+        for (col, cells) in [
+            (0, [0, 1, 0].as_slice()),
+            (1, &[1, 0]),
+            (2, &[1, 0]),
+            (3, &[1, 0]),
+        ] {
+            for (row, &player) in cells.iter().enumerate() {
+                state.board[col * ROWS + row] = Some(player);
+            }
+        }
+        state.recount();
+        let check = apply_action(&mut state, &Connect4Action::PopOut { column: 0 }).unwrap();
+        assert_eq!(
+            check,
+            Connect4Check::Over(Connect4Result::Winner {
+                player: 0,
+                line: vec![(0, 1), (1, 1), (2, 1), (3, 1)]
+            })
+        );
+    }
+
+    #[test]
+    fn display() {
+        let state = Connect4State::from_moves("4453").unwrap();
+        assert_eq!(
+            state.to_string(),
+            "\
+1 2 3 4 5 6 7
+. . . . . . .
+. . . . . . .
+. . . . . . .
+. . . . . . .
+. . . O . . .
+. . O X X . .
+"
+        );
+    }
+}
@@ -0,0 +1,752 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tracing::{event, field, span, Level};
+
+use crate::{
+    apply_action, legal_actions, opening::OpeningBook, undo_action, Agent, Connect4Action,
+    Connect4Check, Connect4Result, Connect4State, Instant, RandomAgent, SearchStats,
+};
+
+struct Node {
+    // Action played to get from the parent to this node, `None` for the root.
+    action: Option<Connect4Action>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // Legal actions that don't have a child node yet.
+    untried: Vec<Connect4Action>,
+    // Player that made the move into this node. `value` is from their point of view.
+    player: usize,
+    visits: u32,
+    value: f32,
+    // Simulations that have been through this node and haven't been backpropagated yet. Each
+    // counts as a visit that lost, so other threads spread out to other branches.
+    virtual_loss: u32,
+    // Set if the game is over at this node.
+    result: Option<Connect4Result>,
+}
+
+impl Node {
+    fn new(
+        state: &Connect4State,
+        action: Option<Connect4Action>,
+        parent: Option<usize>,
+        player: usize,
+        result: Option<Connect4Result>,
+    ) -> Self {
+        let untried = if result.is_some() {
+            vec![]
+        } else {
+            legal_actions(state)
+        };
+        Self {
+            action,
+            parent,
+            children: vec![],
+            untried,
+            player,
+            visits: 0,
+            value: 0.,
+            virtual_loss: 0,
+            result,
+        }
+    }
+}
+
+// Plays randomly from `state` until the game ends, unless it's already over with `result`. The
+// moves are left on `state` for `rewind` to take back.
+fn simulate(
+    state: &mut Connect4State,
+    result: Option<Connect4Result>,
+    agent: &mut RandomAgent,
+) -> Connect4Result {
+    match result {
+        Some(result) => result,
+        None => loop {
+            let action = agent.choose(state).unwrap();
+            if let Connect4Check::Over(result) = apply_action(state, &action).unwrap() {
+                break result;
+            }
+        },
+    }
+}
+
+// Undoes moves until only `played` are left, taking `state` back to the root after a simulation.
+fn rewind(state: &mut Connect4State, played: usize) {
+    while state.history.len() > played {
+        let action = *state.history.last().unwrap();
+        undo_action(state, &action).unwrap();
+    }
+}
+
+// 1 for a win, 0.5 for a tie and 0 for a loss.
+fn reward(result: &Connect4Result, player: usize) -> f32 {
+    match result {
+        Connect4Result::Winner { player: winner, .. } if *winner == player => 1.,
+        Connect4Result::Winner { .. } => 0.,
+        Connect4Result::Tie => 0.5,
+    }
+}
+
+/// What the search found out about one of the moves at the root.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionStats {
+    pub action: Connect4Action,
+    pub visits: u32,
+    /// Average reward for the player making the move, 1 is always winning and 0 always losing.
+    pub win_rate: f32,
+}
+
+impl ActionStats {
+    /// The range the move's real win rate is in with 95% confidence, going by the Wilson score
+    /// interval. It's wide after a few simulations and narrows as the move gets more. A move that
+    /// hasn't been visited could be anything from 0 to 1.
+    pub fn confidence_interval(&self) -> (f32, f32) {
+        if self.visits == 0 {
+            return (0., 1.);
+        }
+        const Z: f32 = 1.96;
+        let n = self.visits as f32;
+        let p = self.win_rate;
+        let scale = 1. + Z * Z / n;
+        let center = (p + Z * Z / (2. * n)) / scale;
+        let spread = Z * (p * (1. - p) / n + Z * Z / (4. * n * n)).sqrt() / scale;
+        ((center - spread).max(0.), (center + spread).min(1.))
+    }
+}
+
+/// UCT search tree. Nodes live in an arena and refer to each other by index, the root is always
+/// at index 0.
+pub struct MctsTree {
+    root: Connect4State,
+    nodes: Vec<Node>,
+    exploration: f32,
+    rng: StdRng,
+    rollout_agent: RandomAgent,
+    // The board `iterate` plays on, back at the root between iterations. Kept so every iteration
+    // doesn't need a copy of the root.
+    board: Option<Connect4State>,
+}
+
+impl MctsTree {
+    pub fn new(state: &Connect4State, exploration: f32, mut rng: StdRng) -> Self {
+        let player = state.config.player_before(state.next_player);
+        let root = Node::new(state, None, None, player, None);
+        let rollout_agent = RandomAgent::seeded(rng.gen());
+        Self {
+            root: state.clone(),
+            nodes: vec![root],
+            exploration,
+            rng,
+            rollout_agent,
+            board: None,
+        }
+    }
+
+    // UCB1 score of a child, from the point of view of the player choosing it. Virtual losses
+    // count as visits that scored nothing.
+    fn ucb1(&self, parent: usize, child: usize) -> f32 {
+        let parent = &self.nodes[parent];
+        let parent_visits = (parent.visits + parent.virtual_loss) as f32;
+        let child = &self.nodes[child];
+        let visits = (child.visits + child.virtual_loss) as f32;
+        child.value / visits + self.exploration * (parent_visits.ln() / visits).sqrt()
+    }
+
+    /// Runs one round of selection, expansion, simulation and backpropagation.
+    pub fn iterate(&mut self) {
+        let mut board = self.board.take().unwrap_or_else(|| self.root.clone());
+        let node = self.select(&mut board);
+        let result = simulate(
+            &mut board,
+            self.nodes[node].result.clone(),
+            &mut self.rollout_agent,
+        );
+        rewind(&mut board, self.root.history.len());
+        self.board = Some(board);
+        self.backpropagate(node, &result);
+    }
+
+    /// Runs simulations on `threads` threads that all share this tree until `budget` runs out.
+    /// Selection, expansion and backpropagation take turns on the tree and the rollouts run at
+    /// the same time, so the search scales with threads as long as rollouts dominate.
+    ///
+    /// Which thread gets to the tree first isn't deterministic, so neither is the search.
+    pub fn search_parallel(&mut self, budget: Budget, threads: usize) {
+        let seeds: Vec<u64> = (0..threads.max(1)).map(|_| self.rng.gen()).collect();
+        let started = AtomicUsize::new(0);
+        let start = Instant::now();
+        let (root, played) = (&self.root.clone(), self.root.history.len());
+        let tree = Mutex::new(self);
+        thread::scope(|scope| {
+            for seed in seeds {
+                let (tree, started) = (&tree, &started);
+                scope.spawn(move || {
+                    let mut agent = RandomAgent::seeded(seed);
+                    // Each thread plays on its own board.
+                    let mut board = root.clone();
+                    while budget.allows(started.fetch_add(1, Ordering::Relaxed), start) {
+                        let (node, result) = {
+                            let mut tree = tree.lock().unwrap();
+                            let node = tree.select(&mut board);
+                            (node, tree.nodes[node].result.clone())
+                        };
+                        let result = simulate(&mut board, result, &mut agent);
+                        rewind(&mut board, played);
+                        tree.lock().unwrap().backpropagate(node, &result);
+                    }
+                });
+            }
+        });
+    }
+
+    // Selection and expansion. Plays the moves down to the new leaf on `state`, which starts at
+    // the root, and returns the leaf with a virtual loss on every node from the root down to it.
+    fn select(&mut self, state: &mut Connect4State) -> usize {
+        let mut node = 0;
+        self.nodes[node].virtual_loss += 1;
+
+        // Selection: walk down fully expanded nodes picking the child with the best UCB1 score.
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = *self.nodes[node]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| self.ucb1(node, a).total_cmp(&self.ucb1(node, b)))
+                .unwrap();
+            let action = self.nodes[node].action.unwrap();
+            apply_action(state, &action).unwrap();
+            self.nodes[node].virtual_loss += 1;
+        }
+
+        // Expansion: add one of the untried moves as a new child.
+        if !self.nodes[node].untried.is_empty() {
+            let untried = &mut self.nodes[node].untried;
+            let action = untried.swap_remove(self.rng.gen_range(0..untried.len()));
+            let player = state.next_player;
+            let result = match apply_action(state, &action).unwrap() {
+                Connect4Check::Over(result) => Some(result),
+                Connect4Check::InProgress => None,
+            };
+            let child = self.nodes.len();
+            self.nodes
+                .push(Node::new(state, Some(action), Some(node), player, result));
+            self.nodes[node].children.push(child);
+            node = child;
+            self.nodes[node].virtual_loss += 1;
+        }
+        node
+    }
+
+    // Backpropagation: update every node on the path back to the root, swapping its virtual loss
+    // for the real result.
+    fn backpropagate(&mut self, leaf: usize, result: &Connect4Result) {
+        let mut current = Some(leaf);
+        while let Some(i) = current {
+            let node = &mut self.nodes[i];
+            node.visits += 1;
+            node.value += reward(result, node.player);
+            node.virtual_loss -= 1;
+            current = node.parent;
+        }
+    }
+
+    /// Moves the root down the tree to `state`, keeping the subtree under it and dropping the
+    /// rest. `state` has to be the current root with more moves played, as found at the end of
+    /// its history. Returns false and leaves the tree alone if it isn't, or if those moves haven't
+    /// been expanded in the tree.
+    pub fn advance_to(&mut self, state: &Connect4State) -> bool {
+        let played = self.root.history.len();
+        if state.config != self.root.config || !state.history.starts_with(&self.root.history) {
+            return false;
+        }
+        // The same moves from another position, like the same game with the other player first,
+        // lead somewhere else.
+        let mut reached = self.root.clone();
+        for action in &state.history[played..] {
+            if apply_action(&mut reached, action).is_err() {
+                return false;
+            }
+        }
+        if reached != *state {
+            return false;
+        }
+        let mut root = 0;
+        for action in &state.history[played..] {
+            let child = self.nodes[root]
+                .children
+                .iter()
+                .find(|&&child| self.nodes[child].action.as_ref() == Some(action));
+            match child {
+                Some(&child) => root = child,
+                None => return false,
+            }
+        }
+
+        // Copy the subtree into a new arena, parents before their children.
+        let mut nodes = Vec::new();
+        let mut queue = VecDeque::from([(root, None)]);
+        while let Some((old, parent)) = queue.pop_front() {
+            let new = nodes.len();
+            let node = &mut self.nodes[old];
+            let children = std::mem::take(&mut node.children);
+            queue.extend(children.into_iter().map(|child| (child, Some(new))));
+            nodes.push(Node {
+                action: node.action.take(),
+                parent,
+                children: vec![],
+                untried: std::mem::take(&mut node.untried),
+                player: node.player,
+                visits: node.visits,
+                value: node.value,
+                virtual_loss: 0,
+                result: node.result.take(),
+            });
+            if let Some(parent) = parent {
+                nodes[parent].children.push(new);
+            }
+        }
+        nodes[0].action = None;
+        self.nodes = nodes;
+        self.root = state.clone();
+        self.board = None;
+        true
+    }
+
+    /// The most visited child of the root. `None` if the search hasn't expanded anything yet.
+    pub fn best_action(&self) -> Option<Connect4Action> {
+        self.nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| self.nodes[child].visits)
+            .map(|&child| self.nodes[child].action.unwrap())
+    }
+
+    /// Stats for every expanded child of the root, drops before pops and in column order.
+    pub fn root_stats(&self) -> Vec<ActionStats> {
+        let mut stats: Vec<_> = self.nodes[0]
+            .children
+            .iter()
+            .map(|&child| {
+                let node = &self.nodes[child];
+                ActionStats {
+                    action: node.action.unwrap(),
+                    visits: node.visits,
+                    win_rate: node.value / node.visits as f32,
+                }
+            })
+            .collect();
+        stats.sort_by_key(|stats| {
+            let pop_out = matches!(stats.action, Connect4Action::PopOut { .. });
+            (pop_out, stats.action.column())
+        });
+        stats
+    }
+}
+
+/// How long the agent searches before picking a move.
+#[derive(Debug, Clone, Copy)]
+pub enum Budget {
+    /// Number of simulations to run per move. Each one is a walk down the tree plus a random
+    /// rollout of at most `rows * cols` moves, so the time per move grows linearly with this and
+    /// is dominated by the rollouts. Doubling it doubles the thinking time.
+    Iterations(usize),
+    /// Keep simulating until this much time has passed. The clock is checked between
+    /// simulations so a move can take slightly longer than this.
+    Time(Duration),
+}
+
+impl Budget {
+    // Whether there's budget left to start simulation `i`, always true for the first one so
+    // there's a move to return.
+    fn allows(&self, i: usize, start: Instant) -> bool {
+        match *self {
+            Budget::Iterations(iterations) => i < iterations.max(1),
+            Budget::Time(budget) => i == 0 || start.elapsed() < budget,
+        }
+    }
+}
+
+/// How `MctsAgent` picks its move from the root's stats once the search is done.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MoveSelection {
+    /// The move the search spent the most simulations on.
+    #[default]
+    MostVisited,
+    /// The move with the highest lower end of its `ActionStats::confidence_interval`, the one
+    /// surest to be good. Safer than the most visited move when there aren't many simulations.
+    LowerBound,
+}
+
+/// Monte-Carlo tree search agent using UCT (UCB1 applied to trees).
+pub struct MctsAgent {
+    pub budget: Budget,
+    /// The exploration constant `c` in UCB1. `sqrt(2)` is the textbook value.
+    pub exploration: f32,
+    /// Threads searching the tree together, 1 by default. With more than one the moves aren't
+    /// reproducible from the seed.
+    pub threads: usize,
+    pub selection: MoveSelection,
+    rng: StdRng,
+    last_stats: Vec<ActionStats>,
+    search_stats: SearchStats,
+    // The tree from the last search, reused for the next move if the game carried on from it.
+    tree: Option<MctsTree>,
+    /// Moves to play without searching when the position is in the book.
+    pub opening_book: Option<OpeningBook>,
+}
+
+impl MctsAgent {
+    pub fn new(iterations: usize, exploration: f32) -> Self {
+        Self {
+            budget: Budget::Iterations(iterations),
+            exploration,
+            threads: 1,
+            selection: MoveSelection::MostVisited,
+            rng: StdRng::from_entropy(),
+            last_stats: vec![],
+            search_stats: SearchStats::default(),
+            tree: None,
+            opening_book: None,
+        }
+    }
+
+    /// Same seed and same opponent moves, same search and same moves.
+    pub fn seeded(iterations: usize, exploration: f32, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            ..Self::new(iterations, exploration)
+        }
+    }
+
+    /// Searches for `budget` every move, for playing against a clock.
+    pub fn with_time_budget(budget: Duration) -> Self {
+        Self {
+            budget: Budget::Time(budget),
+            ..Self::default()
+        }
+    }
+
+    /// The root's stats at the end of the last search, empty before the first move.
+    pub fn last_stats(&self) -> &[ActionStats] {
+        &self.last_stats
+    }
+
+    /// Simulations run and time spent over every move so far.
+    pub fn search_stats(&self) -> SearchStats {
+        self.search_stats
+    }
+}
+
+impl Default for MctsAgent {
+    /// 100 simulations for each column of the standard board, the same budget the old flat
+    /// rollout search used.
+    fn default() -> Self {
+        Self::new(100 * crate::COLS, std::f32::consts::SQRT_2)
+    }
+}
+
+impl Agent for MctsAgent {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        let span = span!(
+            Level::TRACE,
+            "mcts agent turn",
+            column = field::Empty,
+            win_rate = field::Empty,
+            simulations = field::Empty,
+            elapsed_us = field::Empty,
+        )
+        .entered();
+        let start = Instant::now();
+
+        if let Some(action) = self.opening_book.as_ref().and_then(|book| book.get(state)) {
+            event!(Level::TRACE, column = action.column(), "book move");
+            span.record("column", action.column());
+            self.last_stats.clear();
+            return Some(action);
+        }
+
+        // Keep the search below the moves played since the last turn, or start over if the game
+        // isn't the one the tree was for.
+        let reused = self
+            .tree
+            .take()
+            .and_then(|mut tree| tree.advance_to(state).then_some(tree));
+        let mut tree = match reused {
+            Some(mut tree) => {
+                tree.exploration = self.exploration;
+                tree
+            }
+            None => {
+                let rng = StdRng::seed_from_u64(self.rng.gen());
+                MctsTree::new(state, self.exploration, rng)
+            }
+        };
+        let reused_visits = tree.nodes[0].visits;
+        if self.threads > 1 {
+            tree.search_parallel(self.budget, self.threads);
+        } else {
+            for i in (0..).take_while(|&i| self.budget.allows(i, start)) {
+                let _span = span!(Level::TRACE, "mcts iteration", i = i).entered();
+                tree.iterate();
+            }
+        }
+        self.last_stats = tree.root_stats();
+        let simulations = tree.nodes[0].visits - reused_visits;
+        let elapsed = start.elapsed();
+        self.search_stats.record(simulations.into(), elapsed);
+        let action = match self.selection {
+            MoveSelection::MostVisited => tree.best_action(),
+            MoveSelection::LowerBound => self
+                .last_stats
+                .iter()
+                .max_by(|a, b| {
+                    let lower = |stats: &ActionStats| stats.confidence_interval().0;
+                    lower(a).total_cmp(&lower(b))
+                })
+                .map(|stats| stats.action),
+        };
+
+        for stats in &self.last_stats {
+            event!(
+                Level::TRACE,
+                column = stats.action.column(),
+                visits = stats.visits,
+                win_rate = stats.win_rate,
+                "root move"
+            );
+        }
+        if let Some(best) = self
+            .last_stats
+            .iter()
+            .find(|stats| Some(stats.action) == action)
+        {
+            span.record("column", best.action.column());
+            span.record("win_rate", best.win_rate);
+        }
+        span.record("simulations", simulations);
+        span.record("elapsed_us", elapsed.as_micros() as u64);
+        self.tree = Some(tree);
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{check_action, check_state};
+
+    #[test]
+    fn takes_the_winning_move() {
+        let mut state = Connect4State::default();
+        for column in [0, 0, 1, 1, 2, 2] {
+            apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+        }
+        let action = MctsAgent::seeded(500, std::f32::consts::SQRT_2, 0)
+            .choose(&state)
+            .unwrap();
+        assert_eq!(action.column(), 3);
+    }
+
+    #[test]
+    fn last_stats_cover_the_root() {
+        let state = Connect4State::from_moves("112233").unwrap();
+        let mut agent = MctsAgent::seeded(500, std::f32::consts::SQRT_2, 0);
+        assert!(agent.last_stats().is_empty());
+        let action = agent.choose(&state).unwrap();
+
+        let stats = agent.last_stats();
+        let columns: Vec<_> = stats.iter().map(|stats| stats.action.column()).collect();
+        assert_eq!(columns, [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(stats.iter().map(|stats| stats.visits).sum::<u32>(), 500);
+        let best = stats.iter().max_by_key(|stats| stats.visits).unwrap();
+        assert_eq!(best.action, action);
+        assert_eq!(best.win_rate, 1.);
+    }
+
+    #[test]
+    fn plays_book_moves_without_searching() {
+        let mut agent = MctsAgent::seeded(200, std::f32::consts::SQRT_2, 0);
+        agent.opening_book = Some(OpeningBook::standard());
+        let mut state = Connect4State::default();
+        for _ in 0..2 {
+            let action = agent.choose(&state).unwrap();
+            assert_eq!(action, Connect4Action::Drop { column: 3 });
+            apply_action(&mut state, &action).unwrap();
+        }
+        assert_eq!(agent.search_stats().nodes, 0);
+
+        // Out of the book it searches like normal.
+        agent.choose(&state).unwrap();
+        assert_eq!(agent.search_stats().nodes, 200);
+    }
+
+    #[test]
+    fn intervals_narrow_with_visits() {
+        let stats = |visits| ActionStats {
+            action: Connect4Action::Drop { column: 0 },
+            visits,
+            win_rate: 0.5,
+        };
+        let (low, high) = stats(10).confidence_interval();
+        assert!(low < 0.5 && 0.5 < high);
+        let (more_low, more_high) = stats(1000).confidence_interval();
+        assert!(low < more_low && more_high < high);
+        assert_eq!(stats(0).confidence_interval(), (0., 1.));
+    }
+
+    #[test]
+    fn lower_bound_selection_plays_legal_moves() {
+        for seed in 0..10 {
+            let mut agent = MctsAgent::seeded(300, std::f32::consts::SQRT_2, seed);
+            agent.selection = MoveSelection::LowerBound;
+            let state = Connect4State::from_moves("4455").unwrap();
+            let action = agent.choose(&state).unwrap();
+            assert!(check_action(&state, &action));
+            // Every legal move gets expanded with this many simulations.
+            assert_eq!(agent.last_stats().len(), legal_actions(&state).len());
+            let best = agent
+                .last_stats()
+                .iter()
+                .map(|stats| stats.confidence_interval().0)
+                .fold(0., f32::max);
+            let chosen = agent.last_stats().iter().find(|s| s.action == action);
+            assert_eq!(chosen.unwrap().confidence_interval().0, best);
+        }
+    }
+
+    #[test]
+    fn counts_simulations() {
+        let mut state = Connect4State::from_moves("44").unwrap();
+        let mut agent = MctsAgent::seeded(200, std::f32::consts::SQRT_2, 0);
+        assert_eq!(agent.search_stats().nodes, 0);
+        let action = agent.choose(&state).unwrap();
+        assert_eq!(agent.search_stats().nodes, 200);
+        apply_action(&mut state, &action).unwrap();
+        apply_action(&mut state, &Connect4Action::Drop { column: 0 }).unwrap();
+        // Visits kept from the reused tree don't count again.
+        agent.choose(&state).unwrap();
+        let stats = agent.search_stats();
+        assert_eq!(stats.nodes, 400);
+        assert!(stats.elapsed > Duration::ZERO);
+    }
+
+    #[test]
+    fn parallel_search_shares_the_tree() {
+        let state = Connect4State::from_moves("112233").unwrap();
+        let mut tree = MctsTree::new(&state, std::f32::consts::SQRT_2, StdRng::seed_from_u64(0));
+        tree.search_parallel(Budget::Iterations(2000), 4);
+        assert_eq!(tree.best_action(), Some(Connect4Action::Drop { column: 3 }));
+        assert_eq!(tree.nodes[0].visits, 2000);
+        // Every simulation was backpropagated, so no virtual losses are left over.
+        assert!(tree.nodes.iter().all(|node| node.virtual_loss == 0));
+    }
+
+    #[test]
+    fn iterations_leave_the_board_at_the_root() {
+        let state = Connect4State::from_moves_with_config(
+            crate::BoardConfig::default().with_pop_out(),
+            "4455",
+        )
+        .unwrap();
+        let mut tree = MctsTree::new(&state, std::f32::consts::SQRT_2, StdRng::seed_from_u64(0));
+        for _ in 0..200 {
+            tree.iterate();
+            let board = tree.board.as_ref().unwrap();
+            assert_eq!(board, &state);
+            assert_eq!(board.history, state.history);
+            assert_eq!(board.heights, state.heights);
+        }
+    }
+
+    #[test]
+    fn virtual_loss_spreads_selection() {
+        // With a simulation in flight down one branch the next selection picks another one.
+        let state = Connect4State::from_moves("4455").unwrap();
+        let mut tree = MctsTree::new(&state, std::f32::consts::SQRT_2, StdRng::seed_from_u64(0));
+        for _ in 0..100 {
+            tree.iterate();
+        }
+        let first = tree.select(&mut state.clone());
+        let second = tree.select(&mut state.clone());
+        let branch = |mut node: usize| {
+            while tree.nodes[node].parent != Some(0) {
+                node = tree.nodes[node].parent.unwrap();
+            }
+            node
+        };
+        assert_ne!(branch(first), branch(second));
+    }
+
+    #[test]
+    fn reuses_the_tree_for_the_next_move() {
+        let mut state = Connect4State::from_moves("44").unwrap();
+        let mut agent = MctsAgent::seeded(1000, std::f32::consts::SQRT_2, 0);
+        let action = agent.choose(&state).unwrap();
+        apply_action(&mut state, &action).unwrap();
+        let reply = Connect4Action::Drop { column: 0 };
+        apply_action(&mut state, &reply).unwrap();
+
+        // The subtree under the two moves is already searched, so the root starts with visits.
+        let tree = agent.tree.as_ref().unwrap();
+        let after_action = tree.nodes[0]
+            .children
+            .iter()
+            .find(|&&child| tree.nodes[child].action == Some(action))
+            .unwrap();
+        let warm = tree.nodes[*after_action]
+            .children
+            .iter()
+            .find(|&&child| tree.nodes[child].action == Some(reply))
+            .map_or(0, |&child| tree.nodes[child].visits);
+        assert!(warm > 0);
+
+        agent.choose(&state).unwrap();
+        let tree = agent.tree.as_ref().unwrap();
+        assert_eq!(tree.nodes[0].visits, warm + 1000);
+        let children = &tree.nodes[0].children;
+        assert!(children
+            .iter()
+            .all(|&child| tree.nodes[child].parent == Some(0)));
+    }
+
+    #[test]
+    fn rebuilds_the_tree_for_another_game() {
+        let mut agent = MctsAgent::seeded(300, std::f32::consts::SQRT_2, 0);
+        agent.choose(&Connect4State::from_moves("44").unwrap());
+        // Not a continuation of the last position, the second disc went somewhere else.
+        agent.choose(&Connect4State::from_moves("4512").unwrap());
+        assert_eq!(agent.tree.as_ref().unwrap().nodes[0].visits, 300);
+    }
+
+    #[test]
+    fn rebuilds_the_tree_when_the_other_player_started() {
+        let mut agent = MctsAgent::seeded(300, std::f32::consts::SQRT_2, 0);
+        agent.choose(&Connect4State::default());
+        // The same history as a continuation, but it's player 1's disc in column 4.
+        let mut state = Connect4State::with_first_player(Default::default(), 1);
+        apply_action(&mut state, &Connect4Action::Drop { column: 3 }).unwrap();
+        agent.choose(&state);
+        let tree = agent.tree.as_ref().unwrap();
+        assert_eq!(tree.nodes[0].visits, 300);
+        assert_eq!(tree.root, state);
+    }
+
+    #[test]
+    fn time_budget_always_returns_a_legal_move() {
+        let mut state = Connect4State::default();
+        let mut agent = MctsAgent::with_time_budget(Duration::ZERO);
+        while let Connect4Check::InProgress = check_state(&state) {
+            let action = agent.choose(&state).unwrap();
+            assert!(check_action(&state, &action));
+            apply_action(&mut state, &action).unwrap();
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::{collections::HashMap, time::Duration};
+
+use tracing::{event, field, span, Level};
+
+use crate::{
+    apply_action, check_action,
+    eval::{evaluate, WIN_SCORE},
+    legal_actions, ordered_columns, undo_action,
+    zobrist::ZobristHasher,
+    Agent, Connect4Action, Connect4Check, Connect4Result, Connect4State, Instant, SearchStats,
+    COLS, ROWS,
+};
+
+// Legal actions with the central columns first, drops before pop outs.
+fn ordered_actions(state: &Connect4State) -> Vec<Connect4Action> {
+    let order: Vec<_> = ordered_columns(state.config.cols).collect();
+    let mut actions = legal_actions(state);
+    actions.sort_by_key(|action| {
+        let rank = order.iter().position(|&column| column == action.column());
+        (matches!(action, Connect4Action::PopOut { .. }), rank)
+    });
+    actions
+}
+
+// Best moves found so far by position, so a deeper search can try them first.
+type BestMoves = HashMap<u64, Connect4Action>;
+
+struct Search {
+    hasher: ZobristHasher,
+    best_moves: BestMoves,
+    // Give up on the search once this passes.
+    deadline: Option<Instant>,
+    timed_out: bool,
+    nodes: u64,
+}
+
+impl Search {
+    fn new(state: &Connect4State) -> Self {
+        Self {
+            hasher: ZobristHasher::new(state.config),
+            best_moves: BestMoves::new(),
+            deadline: None,
+            timed_out: false,
+            nodes: 0,
+        }
+    }
+
+    // `ordered_actions` with the best move from an earlier search of this position first.
+    fn actions(&self, state: &Connect4State, hash: u64) -> Vec<Connect4Action> {
+        let mut actions = ordered_actions(state);
+        if let Some(best) = self.best_moves.get(&hash) {
+            if let Some(i) = actions.iter().position(|action| action == best) {
+                actions[..=i].rotate_right(1);
+            }
+        }
+        actions
+    }
+
+    /// Negamax search with alpha-beta pruning. Returns the score of `state` for the player to
+    /// move, searching `depth` more plies. `ply` is how far from the root `state` is, a win `ply`
+    /// moves away scores `WIN_SCORE - ply` so the search prefers faster wins and slower losses.
+    /// Once the deadline passes it sets `timed_out` and the score means nothing.
+    fn negamax(
+        &mut self,
+        state: &mut Connect4State,
+        depth: usize,
+        ply: usize,
+        mut alpha: f32,
+        beta: f32,
+    ) -> f32 {
+        self.nodes += 1;
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+            return 0.;
+        }
+        if depth == 0 {
+            return evaluate(state, state.next_player);
+        }
+        let player = state.next_player;
+        let hash = self.hasher.hash(state);
+        let mut best = f32::NEG_INFINITY;
+        let mut best_action = None;
+        for action in self.actions(state, hash) {
+            let score = match apply_action(state, &action).unwrap() {
+                Connect4Check::Over(Connect4Result::Winner { player: winner, .. })
+                    if winner == player =>
+                {
+                    WIN_SCORE - (ply + 1) as f32
+                }
+                // A pop out can finish only someone else's line.
+                Connect4Check::Over(Connect4Result::Winner { .. }) => {
+                    -(WIN_SCORE - (ply + 1) as f32)
+                }
+                Connect4Check::Over(Connect4Result::Tie) => 0.,
+                Connect4Check::InProgress => {
+                    -self.negamax(state, depth - 1, ply + 1, -beta, -alpha)
+                }
+            };
+            undo_action(state, &action).unwrap();
+            if self.timed_out {
+                return 0.;
+            }
+            if score > best {
+                best = score;
+                best_action = Some(action);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        if let Some(action) = best_action {
+            self.best_moves.insert(hash, action);
+        }
+        best
+    }
+
+    // The best moves found from `state` on, checking each is legal in the position it's played
+    // in. Stops at the end of the game, a position it has no move for, after `depth` moves or
+    // before a pop out, which a column alone doesn't describe.
+    fn principal_variation(&self, state: &Connect4State, depth: usize) -> Vec<usize> {
+        let mut state = state.clone();
+        let mut line = vec![];
+        while line.len() < depth {
+            let Some(&action) = self.best_moves.get(&self.hasher.hash(&state)) else {
+                break;
+            };
+            let Connect4Action::Drop { column } = action else {
+                break;
+            };
+            if !check_action(&state, &action) {
+                break;
+            }
+            line.push(column);
+            if let Connect4Check::Over(_) = apply_action(&mut state, &action).unwrap() {
+                break;
+            }
+        }
+        line
+    }
+
+    // Picks the best move at the root searching `depth` plies, with its score. `None` if the
+    // search ran out of time first.
+    fn root(&mut self, state: &mut Connect4State, depth: usize) -> Option<(Connect4Action, f32)> {
+        // The root counts as a node too.
+        self.nodes += 1;
+        let player = state.next_player;
+        let hash = self.hasher.hash(state);
+        let mut best: Option<(Connect4Action, f32)> = None;
+        for action in self.actions(state, hash) {
+            let alpha = best.map_or(f32::NEG_INFINITY, |(_, score)| score);
+            let score = match apply_action(state, &action).unwrap() {
+                Connect4Check::Over(Connect4Result::Winner { player: winner, .. })
+                    if winner == player =>
+                {
+                    WIN_SCORE - 1.
+                }
+                Connect4Check::Over(Connect4Result::Winner { .. }) => -(WIN_SCORE - 1.),
+                Connect4Check::Over(Connect4Result::Tie) => 0.,
+                Connect4Check::InProgress => {
+                    -self.negamax(state, depth.max(1) - 1, 1, f32::NEG_INFINITY, -alpha)
+                }
+            };
+            undo_action(state, &action).unwrap();
+            if self.timed_out {
+                return None;
+            }
+            event!(
+                Level::TRACE,
+                column = action.column(),
+                score,
+                depth,
+                "root move"
+            );
+            // Strictly better, so ties go to the first move searched. That's the most central
+            // column, or the best move from the last depth when deepening.
+            if best.is_none_or(|(_, best)| score > best) {
+                best = Some((action, score));
+            }
+        }
+        if let Some((action, _)) = best {
+            self.best_moves.insert(hash, action);
+        }
+        best
+    }
+}
+
+/// Deterministic alpha-beta agent. It searches `depth` plies, or with a time budget it searches
+/// 1 ply, then 2, then 3 and so on until time runs out and plays the best move from the deepest
+/// search that finished. Each search tries the best moves from the one before first, so the
+/// deeper ones cut off sooner.
+///
+/// The search is for two players. With more it plays as if each player's only opponent is the one
+/// moving after them.
+pub struct MinimaxAgent {
+    /// With a time budget, the deepest it searches even if there's time left.
+    pub depth: usize,
+    pub budget: Option<Duration>,
+    search_stats: SearchStats,
+    principal_variation: Vec<usize>,
+}
+
+impl MinimaxAgent {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            budget: None,
+            search_stats: SearchStats::default(),
+            principal_variation: vec![],
+        }
+    }
+
+    /// Deepens until `budget` runs out, for playing against a clock. The first ply is always
+    /// searched so there's a move to play, even if that takes longer than `budget`.
+    pub fn with_time_budget(budget: Duration) -> Self {
+        Self {
+            depth: ROWS * COLS,
+            budget: Some(budget),
+            search_stats: SearchStats::default(),
+            principal_variation: vec![],
+        }
+    }
+
+    /// Positions searched and time spent over every move so far.
+    pub fn search_stats(&self) -> SearchStats {
+        self.search_stats
+    }
+
+    /// The line of play the last search expects, as the columns dropped in starting with the
+    /// move it picked. It's as long as the deepest search that finished or until the game ends.
+    pub fn principal_variation(&self) -> &[usize] {
+        &self.principal_variation
+    }
+}
+
+impl Agent for MinimaxAgent {
+    fn choose(&mut self, state: &Connect4State) -> Option<Connect4Action> {
+        let span = span!(
+            Level::TRACE,
+            "minimax agent turn",
+            depth = field::Empty,
+            column = field::Empty,
+            score = field::Empty,
+            nodes = field::Empty,
+            elapsed_us = field::Empty,
+        )
+        .entered();
+        let start = Instant::now();
+        let mut state = state.clone();
+        let mut search = Search::new(&state);
+        let depths = match self.budget {
+            Some(_) => 1..=self.depth.max(1),
+            None => self.depth..=self.depth,
+        };
+        let mut best = None;
+        for depth in depths {
+            match search.root(&mut state, depth) {
+                Some((action, score)) => best = Some((action, score, depth)),
+                None => break,
+            }
+            // Later searches that don't finish overwrite some of the best moves, so take the line
+            // now.
+            self.principal_variation = search.principal_variation(&state, depth);
+            // Only the first search runs without a deadline, it's the fallback.
+            search.deadline = self.budget.map(|budget| start + budget);
+            // A forced win or loss won't change with more depth.
+            if best.is_some_and(|(_, score, _)| score.abs() > WIN_SCORE / 2.) {
+                break;
+            }
+        }
+
+        let elapsed = start.elapsed();
+        self.search_stats.record(search.nodes, elapsed);
+        if let Some((action, score, depth)) = best {
+            span.record("column", action.column());
+            span.record("score", score);
+            span.record("depth", depth);
+        }
+        span.record("nodes", search.nodes);
+        span.record("elapsed_us", elapsed.as_micros() as u64);
+        best.map(|(action, _, _)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoardConfig;
+
+    #[test]
+    fn takes_the_win() {
+        let state = Connect4State::from_moves("112233").unwrap();
+        let action = MinimaxAgent::new(4).choose(&state).unwrap();
+        assert_eq!(action.column(), 3);
+    }
+
+    #[test]
+    fn blocks_the_loss() {
+        // Player 1 threatens to finish the bottom row in column 4 (1-indexed).
+        let state = Connect4State::from_moves("717263").unwrap();
+        let action = MinimaxAgent::new(2).choose(&state).unwrap();
+        assert_eq!(action.column(), 3);
+    }
+
+    #[test]
+    fn scores_depend_on_distance_to_the_win() {
+        let mut now = Connect4State::from_moves("112233").unwrap();
+        let score = Search::new(&now).negamax(&mut now, 5, 0, f32::NEG_INFINITY, f32::INFINITY);
+        assert_eq!(score, WIN_SCORE - 1.);
+
+        // Extending the open two in the bottom row wins on the player's next move.
+        let mut later = Connect4State::from_moves("3747").unwrap();
+        let score = Search::new(&later).negamax(&mut later, 5, 0, f32::NEG_INFINITY, f32::INFINITY);
+        assert_eq!(score, WIN_SCORE - 3.);
+    }
+
+    #[test]
+    fn popping_out_a_line_for_the_other_player_loses() {
+        // A full board where the only move is popping the first column, which drops player 1's disc
+        // into the bottom row next to two more.
+        let config = BoardConfig::new(2, 3, 3).unwrap().with_pop_out();
+        let mut state = Connect4State::new(config);
+        state.board = [0, 1, 1, 0, 1, 0].map(Some).to_vec();
+        state.recount();
+        let (action, score) = Search::new(&state).root(&mut state.clone(), 2).unwrap();
+        assert_eq!(action, Connect4Action::PopOut { column: 0 });
+        assert_eq!(score, -(WIN_SCORE - 1.));
+    }
+
+    #[test]
+    fn counts_nodes() {
+        // Depth 1 looks at the root and each of its seven moves.
+        let state = Connect4State::default();
+        let mut agent = MinimaxAgent::new(1);
+        agent.choose(&state);
+        assert_eq!(agent.search_stats().nodes, 8);
+
+        let mut deeper = MinimaxAgent::new(3);
+        deeper.choose(&state);
+        assert!(deeper.search_stats().nodes > 8 * 7);
+    }
+
+    #[test]
+    fn deepening_matches_a_fixed_depth_search() {
+        // Without a deadline deepening to depth 4 ends on the same search as depth 4, give or
+        // take which of two equally good moves it prefers.
+        let state = Connect4State::from_moves("4453").unwrap();
+        let mut state_copy = state.clone();
+        let mut fixed = Search::new(&state);
+        let (_, fixed_score) = fixed.root(&mut state_copy, 4).unwrap();
+        let mut deepening = Search::new(&state);
+        for depth in 1..=4 {
+            let (_, score) = deepening.root(&mut state_copy, depth).unwrap();
+            if depth == 4 {
+                assert_eq!(score, fixed_score);
+            }
+        }
+    }
+
+    #[test]
+    fn principal_variation_plays_out_the_win() {
+        // Extending the two in the bottom row threatens both ends, the reply can only block one.
+        let mut state = Connect4State::from_moves("3747").unwrap();
+        let mut agent = MinimaxAgent::new(5);
+        let action = agent.choose(&state).unwrap();
+        let line = agent.principal_variation().to_vec();
+        assert_eq!(line[0], action.column());
+        assert_eq!(line.len(), 3);
+        let mut check = Connect4Check::InProgress;
+        for column in line {
+            check = apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+        }
+        assert!(matches!(
+            check,
+            Connect4Check::Over(Connect4Result::Winner { player: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn time_budget_returns_a_move() {
+        let state = Connect4State::from_moves("4453").unwrap();
+        let mut agent = MinimaxAgent::with_time_budget(Duration::ZERO);
+        let action = agent.choose(&state).unwrap();
+        assert!(crate::check_action(&state, &action));
+
+        // With time to think it finds the win in the corner like the fixed depth search.
+        let state = Connect4State::from_moves("112233").unwrap();
+        let mut agent = MinimaxAgent::with_time_budget(Duration::from_millis(50));
+        assert_eq!(agent.choose(&state).unwrap().column(), 3);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    apply_action, check_action, mirror, parse_moves, ActionError, Connect4Action, Connect4State,
+};
+
+#[derive(Error, Debug)]
+pub enum OpeningBookError {
+    #[error("The book has an illegal line. {0}")]
+    Moves(#[from] ActionError),
+    #[error("The book's reply to `{0}` has to be exactly one move. Got `{1}`.")]
+    NotOneMove(String, String),
+}
+
+/// Moves to play in known positions instead of searching, for the start of the game where the
+/// theory is known and searching is slowest.
+#[derive(Debug, Default, Clone)]
+pub struct OpeningBook {
+    moves: HashMap<Connect4State, Connect4Action>,
+}
+
+impl OpeningBook {
+    /// Builds a book for the standard board from `(moves, reply)` pairs, both written like
+    /// `Connect4State::from_moves`: play `reply` after `moves`. Positions are looked up by the
+    /// board, so a reply also covers the other move orders that reach it and the mirrored board.
+    pub fn from_moves_table(table: &[(&str, &str)]) -> Result<Self, OpeningBookError> {
+        let mut book = Self::default();
+        for &(moves, reply) in table {
+            let state = Connect4State::from_moves(moves)?;
+            let action = match parse_moves(reply)?[..] {
+                [action] => action,
+                _ => {
+                    return Err(OpeningBookError::NotOneMove(
+                        moves.to_string(),
+                        reply.to_string(),
+                    ))
+                }
+            };
+            // Checks the reply is legal too.
+            let mut after = state.clone();
+            apply_action(&mut after, &action)?;
+
+            let mirrored = *mirror(&after).history.last().unwrap();
+            book.moves.insert(mirror(&state), mirrored);
+            book.moves.insert(state, action);
+        }
+        Ok(book)
+    }
+
+    /// Start in the center and answer the center with the center. The first player wins with
+    /// perfect play from the center column and can't from any other.
+    pub fn standard() -> Self {
+        Self::from_moves_table(&[("", "4"), ("4", "4")]).unwrap()
+    }
+
+    /// The book's move for `state`, if it has one.
+    pub fn get(&self, state: &Connect4State) -> Option<Connect4Action> {
+        self.moves
+            .get(state)
+            .copied()
+            .filter(|action| check_action(state, action))
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_transpositions_and_mirrors() {
+        let book = OpeningBook::from_moves_table(&[("4453", "6")]).unwrap();
+        let play = |moves| book.get(&Connect4State::from_moves(moves).unwrap());
+        assert_eq!(play("4453"), Some(Connect4Action::Drop { column: 5 }));
+        assert_eq!(play("5344"), Some(Connect4Action::Drop { column: 5 }));
+        assert_eq!(play("4435"), Some(Connect4Action::Drop { column: 1 }));
+        assert_eq!(play("4455"), None);
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!(matches!(
+            OpeningBook::from_moves_table(&[("111111", "1")]),
+            Err(OpeningBookError::Moves(ActionError::FullColumn(0)))
+        ));
+        assert!(matches!(
+            OpeningBook::from_moves_table(&[("4", "44")]),
+            Err(OpeningBookError::NotOneMove(..))
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    apply_action, parse_moves, replay::Replay, ActionError, Agent, BoardConfig, Connect4Result,
+    Connect4State,
+};
+
+#[derive(Error, Debug)]
+pub enum GameFileError {
+    #[error("Couldn't read or write the game file. {0}")]
+    Io(#[from] io::Error),
+    #[error("The game file isn't a valid game record. {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A finished game, everything needed to look at it again later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub config: BoardConfig,
+    /// Records saved before the first player could change always started with player 0.
+    #[serde(default)]
+    pub first_player: usize,
+    /// The moves in the same notation as `Connect4State::from_moves`.
+    pub moves: String,
+    /// Names of the agents playing player 0 and player 1.
+    pub blue: String,
+    pub red: String,
+    pub result: Connect4Result,
+}
+
+impl GameRecord {
+    pub fn new(
+        state: &Connect4State,
+        blue: impl Into<String>,
+        red: impl Into<String>,
+        result: Connect4Result,
+    ) -> Self {
+        Self {
+            config: state.config,
+            first_player: state.first_player(),
+            moves: state.to_moves(),
+            blue: blue.into(),
+            red: red.into(),
+            result,
+        }
+    }
+
+    /// `new` with the agents' `Agent::name`s.
+    pub fn from_agents<B, R>(
+        state: &Connect4State,
+        blue_agent: &B,
+        red_agent: &R,
+        result: Connect4Result,
+    ) -> Self
+    where
+        B: Agent + ?Sized,
+        R: Agent + ?Sized,
+    {
+        Self::new(state, blue_agent.name(), red_agent.name(), result)
+    }
+
+    /// Plays the moves again to get the final position, history included.
+    pub fn replay(&self) -> Result<Connect4State, ActionError> {
+        let mut state = self.start();
+        for action in parse_moves(&self.moves)? {
+            apply_action(&mut state, &action)?;
+        }
+        Ok(state)
+    }
+
+    /// The empty board the game started from.
+    pub fn start(&self) -> Connect4State {
+        Connect4State::with_first_player(self.config, self.first_player)
+    }
+
+    /// Steps through the game from the empty board. The moves are only checked as they're
+    /// played.
+    pub fn steps(&self) -> Result<Replay, ActionError> {
+        Ok(Replay::new(self.start(), parse_moves(&self.moves)?))
+    }
+}
+
+/// Writes `record` to `path` as JSON, replacing the file if there is one.
+pub fn save_game(path: impl AsRef<Path>, record: &GameRecord) -> Result<(), GameFileError> {
+    let json = serde_json::to_string_pretty(record)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+pub fn load_game(path: impl AsRef<Path>) -> Result<GameRecord, GameFileError> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{play, RandomAgent};
+
+    #[test]
+    fn save_and_load() {
+        let mut state = Connect4State::default();
+        let mut blue = RandomAgent::seeded(1).named("random 1");
+        let mut red = RandomAgent::seeded(2);
+        let outcome = play(&mut state, &mut blue, &mut red).unwrap();
+        let record = GameRecord::from_agents(&state, &blue, &red, outcome.result);
+        assert_eq!(
+            (record.blue.as_str(), record.red.as_str()),
+            ("random 1", "RandomAgent")
+        );
+
+        let path = std::env::temp_dir().join(format!("connect4-{}.json", std::process::id()));
+        save_game(&path, &record).unwrap();
+        let loaded = load_game(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, record);
+
+        let replayed = loaded.replay().unwrap();
+        assert_eq!(replayed.board, state.board);
+        assert_eq!(replayed.next_player, state.next_player);
+        assert_eq!(replayed.history, state.history);
+        assert_eq!(loaded.steps().unwrap().count(), state.history.len());
+    }
+
+    #[test]
+    fn keeps_the_first_player() {
+        let mut state = Connect4State::with_first_player(BoardConfig::default(), 1);
+        apply_action(&mut state, &crate::Connect4Action::Drop { column: 2 }).unwrap();
+        let record = GameRecord::new(&state, "a", "b", Connect4Result::Tie);
+        let json = serde_json::to_string(&record).unwrap();
+        let loaded: GameRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.replay().unwrap().cell(2, 0), Some(1));
+
+        // Older records without the field started with player 0.
+        let old = json.replace(r#""first_player":1,"#, "");
+        let loaded: GameRecord = serde_json::from_str(&old).unwrap();
+        assert_eq!(loaded.first_player, 0);
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = std::env::temp_dir().join("connect4-no-such-game.json");
+        assert!(matches!(load_game(path), Err(GameFileError::Io(_))));
+    }
+}
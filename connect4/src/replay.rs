@@ -0,0 +1,154 @@
+use crate::{apply_action, undo_action, ActionError, Connect4Action, Connect4State};
+
+/// Steps forward and back through a list of moves from the start of a game, for looking over a
+/// recorded game. As an iterator it goes forward from the current position and yields the
+/// position before each move with the move played from it.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    state: Connect4State,
+    moves: Vec<Connect4Action>,
+    // Length of the start position's history, moves before it aren't part of the replay.
+    start: usize,
+}
+
+impl Replay {
+    /// `start` is the position before `moves`, usually an empty board.
+    pub fn new(start: Connect4State, moves: Vec<Connect4Action>) -> Self {
+        Self {
+            start: start.history.len(),
+            state: start,
+            moves,
+        }
+    }
+
+    /// The position after every move played so far.
+    pub fn state(&self) -> &Connect4State {
+        &self.state
+    }
+
+    /// How many moves have been played, 0 at the start.
+    pub fn played(&self) -> usize {
+        self.state.history.len() - self.start
+    }
+
+    pub fn moves(&self) -> &[Connect4Action] {
+        &self.moves
+    }
+
+    /// Plays the next move and returns it. `None` at the end of the game. A move that isn't
+    /// legal is an error and the position stays where it was.
+    pub fn forward(&mut self) -> Result<Option<Connect4Action>, ActionError> {
+        let Some(&action) = self.moves.get(self.played()) else {
+            return Ok(None);
+        };
+        apply_action(&mut self.state, &action)?;
+        Ok(Some(action))
+    }
+
+    /// Takes back the last move played and returns it. `None` at the start of the game.
+    pub fn back(&mut self) -> Option<Connect4Action> {
+        if self.played() == 0 {
+            return None;
+        }
+        let action = *self.state.history.last()?;
+        undo_action(&mut self.state, &action).expect("replayed moves can be undone");
+        Some(action)
+    }
+
+    /// Goes straight to the position after `played` moves, or as close as it gets before an
+    /// illegal move.
+    pub fn seek(&mut self, played: usize) -> Result<(), ActionError> {
+        while self.played() > played {
+            self.back();
+        }
+        while self.played() < played.min(self.moves.len()) {
+            self.forward()?;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Replay {
+    type Item = Result<(Connect4State, Connect4Action), ActionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let before = self.state.clone();
+        match self.forward() {
+            Ok(Some(action)) => Some(Ok((before, action))),
+            Ok(None) => None,
+            Err(error) => {
+                // Nothing after an illegal move can be played, so stop there.
+                self.moves.truncate(self.played());
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_moves;
+
+    #[test]
+    fn forward_and_back() {
+        let moves = parse_moves("4453").unwrap();
+        let mut replay = Replay::new(Connect4State::default(), moves.clone());
+        for action in &moves {
+            assert_eq!(replay.forward().unwrap(), Some(*action));
+        }
+        assert_eq!(replay.forward().unwrap(), None);
+        assert_eq!(
+            replay.state().board,
+            Connect4State::from_moves("4453").unwrap().board
+        );
+
+        assert_eq!(replay.back(), Some(moves[3]));
+        assert_eq!(replay.state().to_moves(), "445");
+        replay.seek(1).unwrap();
+        assert_eq!(replay.state().to_moves(), "4");
+        replay.seek(0).unwrap();
+        assert_eq!(replay.back(), None);
+        replay.seek(10).unwrap();
+        assert_eq!(replay.played(), 4);
+    }
+
+    #[test]
+    fn starts_from_a_game_in_progress() {
+        let start = Connect4State::from_moves("44").unwrap();
+        let mut replay = Replay::new(start, parse_moves("3").unwrap());
+        assert_eq!(replay.back(), None);
+        replay.forward().unwrap();
+        assert_eq!(replay.played(), 1);
+        assert_eq!(replay.state().to_moves(), "443");
+    }
+
+    #[test]
+    fn iterates_positions_before_each_move() {
+        let replay = Replay::new(Connect4State::default(), parse_moves("123").unwrap());
+        let steps: Vec<_> = replay
+            .map(|step| {
+                let (state, action) = step.unwrap();
+                (state.to_moves(), action.column())
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            [("".into(), 0), ("1".into(), 1), ("12".into(), 2)] as [(String, usize); 3]
+        );
+    }
+
+    #[test]
+    fn illegal_moves_are_errors() {
+        // The seventh disc doesn't fit in the column.
+        let mut replay = Replay::new(Connect4State::default(), parse_moves("11111112").unwrap());
+        replay.seek(6).unwrap();
+        assert!(matches!(replay.forward(), Err(ActionError::FullColumn(0))));
+        assert_eq!(replay.played(), 6);
+
+        let replay = Replay::new(Connect4State::default(), parse_moves("11111112").unwrap());
+        let steps: Vec<_> = replay.collect();
+        assert_eq!(steps.len(), 7);
+        assert!(steps[6].is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::io::{self, Write};
+
+use rayon::prelude::*;
+
+use crate::{
+    apply_action, features::planes, mcts::MctsAgent, play, Agent, Connect4Result, Connect4State,
+};
+
+/// One position from a self-play game, for training a value or policy network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingRecord {
+    /// The position before the move, see `board_tensor`.
+    pub board: Vec<f32>,
+    /// The column the player to move played. Pop outs are recorded by their column too.
+    pub column: usize,
+    /// How the game ended for the player to move, 1 for a win, 0 for a tie and -1 for a loss.
+    pub outcome: f32,
+}
+
+/// The board as two `planes`, the first for the player to move, so the same position looks the
+/// same whoever is playing.
+pub fn board_tensor(state: &Connect4State) -> Vec<f32> {
+    planes(state, state.next_player)
+}
+
+/// Plays a game on the standard board and returns a record for every move, in order.
+pub fn self_play_game(blue: &mut dyn Agent, red: &mut dyn Agent) -> Vec<TrainingRecord> {
+    let mut state = Connect4State::default();
+    let game = play(&mut state, blue, red).unwrap();
+    let result = game.result;
+    let outcome = |player: usize| match &result {
+        Connect4Result::Winner { player: winner, .. } if *winner == player => 1.,
+        Connect4Result::Winner { .. } => -1.,
+        Connect4Result::Tie => 0.,
+    };
+
+    // Replay the game to get the position before each move.
+    let mut replay = Connect4State::default();
+    let mut records = Vec::with_capacity(game.history.len());
+    for action in &game.history {
+        records.push(TrainingRecord {
+            board: board_tensor(&replay),
+            column: action.column(),
+            outcome: outcome(replay.next_player),
+        });
+        apply_action(&mut replay, action).unwrap();
+    }
+    records
+}
+
+/// Plays `games` games of MCTS against itself in parallel, searching `iterations` simulations a
+/// move. The agents in game `i` get seeds made from `seed + i`, so the same arguments make the
+/// same data.
+pub fn self_play(games: usize, iterations: usize, seed: u64) -> Vec<TrainingRecord> {
+    let exploration = std::f32::consts::SQRT_2;
+    let games: Vec<_> = (0..games as u64)
+        .into_par_iter()
+        .map(|i| {
+            let seed = seed.wrapping_add(i).wrapping_mul(2);
+            self_play_game(
+                &mut MctsAgent::seeded(iterations, exploration, seed),
+                &mut MctsAgent::seeded(iterations, exploration, seed + 1),
+            )
+        })
+        .collect();
+    games.concat()
+}
+
+/// Writes the records as CSV with a header row. Each row has the board tensor in columns
+/// `board_0` to `board_{n-1}`, then `column` and `outcome`.
+pub fn write_csv(records: &[TrainingRecord], mut out: impl Write) -> io::Result<()> {
+    let Some(first) = records.first() else {
+        return Ok(());
+    };
+    let header: Vec<_> = (0..first.board.len())
+        .map(|i| format!("board_{i}"))
+        .chain(["column".into(), "outcome".into()])
+        .collect();
+    writeln!(out, "{}", header.join(","))?;
+    for record in records {
+        let row: Vec<_> = record
+            .board
+            .iter()
+            .map(|cell| cell.to_string())
+            .chain([record.column.to_string(), record.outcome.to_string()])
+            .collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{check_state, Connect4Action, Connect4Check, RandomAgent};
+
+    #[test]
+    fn tensor_is_from_the_movers_side() {
+        // Player 0 in column 4 and player 1 in column 5, player 0 to move.
+        let state = Connect4State::from_moves("45").unwrap();
+        let tensor = board_tensor(&state);
+        assert_eq!(tensor.len(), 84);
+        assert_eq!(tensor[3 * 6], 1.);
+        assert_eq!(tensor[42 + 4 * 6], 1.);
+        assert_eq!(tensor.iter().sum::<f32>(), 2.);
+
+        // The same discs from player 1's side.
+        let state = Connect4State::from_moves("454").unwrap();
+        let tensor = board_tensor(&state);
+        assert_eq!(tensor[4 * 6], 1.);
+        assert_eq!(tensor[42 + 3 * 6], 1.);
+        assert_eq!(tensor[42 + 3 * 6 + 1], 1.);
+    }
+
+    #[test]
+    fn records_replay_the_game() {
+        let records = self_play_game(&mut RandomAgent::seeded(1), &mut RandomAgent::seeded(2));
+        let mut state = Connect4State::default();
+        for record in &records {
+            assert_eq!(record.board, board_tensor(&state));
+            let action = Connect4Action::Drop {
+                column: record.column,
+            };
+            apply_action(&mut state, &action).unwrap();
+        }
+        let Connect4Check::Over(result) = check_state(&state) else {
+            panic!("the game isn't over");
+        };
+        // The last move won it, unless the board filled up.
+        let last = records.last().unwrap();
+        match result {
+            Connect4Result::Winner { .. } => assert_eq!(last.outcome, 1.),
+            Connect4Result::Tie => assert_eq!(last.outcome, 0.),
+        }
+        // Players alternate so the outcome flips every move.
+        for pair in records.windows(2) {
+            assert_eq!(pair[0].outcome, -pair[1].outcome);
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let records = self_play(2, 10, 0);
+        assert_eq!(records, self_play(2, 10, 0));
+        let mut out = vec![];
+        write_csv(&records, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), records.len() + 1);
+        assert!(lines[0].starts_with("board_0,board_1,"));
+        assert!(lines[0].ends_with(",board_83,column,outcome"));
+        assert_eq!(lines[1].split(',').count(), 86);
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use crate::{
+    apply_action,
+    bitboard::{BitBoard, BitBoardError, BOTTOM_ROW, HEIGHT},
+    ordered_columns, Connect4Action, Connect4State, COLS, ROWS,
+};
+
+// Scores are `WIN - n` for a win with the `n`th disc on the board, from the point of view of the
+// player to move. They only depend on the position, not on how far the search is from the root,
+// so they can go straight in the transposition table.
+const WIN: i32 = (ROWS * COLS) as i32 + 1;
+// Every cell a disc can go in.
+const BOARD: u64 = BOTTOM_ROW * ((1 << ROWS) - 1);
+
+/// The game-theoretic value of a position for the player to move, with perfect play from both
+/// sides. `plies` counts moves from now up to and including the winning one, so a win on this
+/// move is 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Win { plies: usize },
+    Loss { plies: usize },
+    Draw,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    pub value: Value,
+    /// A column that keeps the value, the quickest win or slowest loss. `None` if the game is
+    /// already over.
+    pub best_move: Option<usize>,
+}
+
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+// Empty cells where `player` would have four in a row with one more disc, whether or not the
+// cell can be played yet.
+fn winning_cells(player: u64, occupied: u64) -> u64 {
+    // Three stacked below.
+    let mut cells = (player << 1) & (player << 2) & (player << 3);
+    // Horizontal and both diagonals, with the gap at either end or in either middle spot.
+    for shift in [HEIGHT, HEIGHT - 1, HEIGHT + 1] {
+        let pairs = (player << shift) & (player << (2 * shift));
+        cells |= pairs & (player << (3 * shift));
+        cells |= pairs & (player >> shift);
+        let pairs = (player >> shift) & (player >> (2 * shift));
+        cells |= pairs & (player << shift);
+        cells |= pairs & (player >> (3 * shift));
+    }
+    cells & BOARD & !occupied
+}
+
+// The lowest empty cell of every column that isn't full.
+fn playable(occupied: u64) -> u64 {
+    (occupied + BOTTOM_ROW) & BOARD
+}
+
+fn column_mask(col: usize) -> u64 {
+    ((1 << ROWS) - 1) << (col * HEIGHT)
+}
+
+// Flips every column of bits, sentinel included, left to right.
+fn mirror(bits: u64) -> u64 {
+    let column = (1 << HEIGHT) - 1;
+    (0..COLS).fold(0, |mirrored, col| {
+        let bits = (bits >> (col * HEIGHT)) & column;
+        mirrored | bits << ((COLS - 1 - col) * HEIGHT)
+    })
+}
+
+struct Solver {
+    table: HashMap<u64, (i32, Bound)>,
+    order: Vec<usize>,
+}
+
+impl Solver {
+    // Negamax with alpha-beta pruning. `player` is the discs of the player to move.
+    fn negamax(&mut self, player: u64, occupied: u64, mut alpha: i32, mut beta: i32) -> i32 {
+        let moves = occupied.count_ones() as i32;
+        let possible = playable(occupied);
+        if winning_cells(player, occupied) & possible != 0 {
+            return WIN - (moves + 1);
+        }
+        if moves as usize == ROWS * COLS {
+            return 0;
+        }
+
+        // Without a win now the opponent has to be stopped from winning next move. Two threats
+        // can't both be stopped, and a disc right under one of their threats lets them play it.
+        let opponent = player ^ occupied;
+        let threats = winning_cells(opponent, occupied);
+        let forced = possible & threats;
+        let mut candidates = if forced != 0 { forced } else { possible };
+        if forced.count_ones() > 1 {
+            return -(WIN - (moves + 2));
+        }
+        candidates &= !(threats >> 1);
+        if candidates == 0 {
+            return -(WIN - (moves + 2));
+        }
+
+        // The best we can do now is win with our next disc after this one, or a tie if the board
+        // fills up first.
+        let most = if moves as usize + 3 <= ROWS * COLS {
+            WIN - (moves + 3)
+        } else {
+            0
+        };
+        beta = beta.min(most);
+        // And the opponent can't win before their next disc.
+        alpha = alpha.max(-(WIN - (moves + 2)));
+        if alpha >= beta {
+            return beta;
+        }
+
+        // A position and its mirror have the same score so they share an entry. The addition
+        // carries at most into each column's sentinel bit, so the key mirrors column by column too.
+        let key = player + occupied;
+        let key = key.min(mirror(key));
+        if let Some(&(score, bound)) = self.table.get(&key) {
+            match bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+
+        // Moves that make the most new threats first, ties broken by the center first order.
+        let mut children: Vec<_> = self
+            .order
+            .iter()
+            .filter_map(|&col| {
+                let cell = candidates & column_mask(col);
+                (cell != 0).then(|| {
+                    let threats = winning_cells(player | cell, occupied | cell).count_ones();
+                    (cell, threats)
+                })
+            })
+            .collect();
+        children.sort_by_key(|&(_, threats)| std::cmp::Reverse(threats));
+
+        let original_alpha = alpha;
+        let mut best = i32::MIN;
+        for (cell, _) in children {
+            let score = -self.negamax(opponent, occupied | cell, -beta, -alpha);
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(key, (best, bound));
+        best
+    }
+}
+
+/// Works out who wins `state` with perfect play and how soon, searching to the end of the game.
+/// That's fast for positions well into the game, with a dozen or more discs on the board, and
+/// can take a long time for positions close to the start.
+///
+/// Only the standard board is supported.
+pub fn solve(state: &Connect4State) -> Result<Solution, BitBoardError> {
+    let bitboard = BitBoard::try_from(state)?;
+    let over = |value| Solution {
+        value,
+        best_move: None,
+    };
+    if bitboard.winner().is_some() {
+        return Ok(over(Value::Loss { plies: 0 }));
+    }
+    if bitboard.is_full() {
+        return Ok(over(Value::Draw));
+    }
+
+    let mut solver = Solver {
+        table: HashMap::new(),
+        order: ordered_columns(COLS).collect(),
+    };
+    let player = bitboard.players[bitboard.next_player];
+    let occupied = bitboard.occupied();
+    let moves = occupied.count_ones() as i32;
+    let mut best: Option<(usize, i32)> = None;
+    for col in ordered_columns(COLS) {
+        let cell = playable(occupied) & column_mask(col);
+        if cell == 0 {
+            continue;
+        }
+        let score = if BitBoard::is_win(player | cell) {
+            WIN - (moves + 1)
+        } else {
+            let alpha = best.map_or(-WIN, |(_, score)| score);
+            -solver.negamax(player ^ occupied, occupied | cell, -WIN, -alpha)
+        };
+        if best.is_none_or(|(_, best)| score > best) {
+            best = Some((col, score));
+        }
+    }
+
+    let (col, score) = best.expect("a board that isn't full has a move");
+    let value = match score {
+        0 => Value::Draw,
+        score if score > 0 => Value::Win {
+            plies: (WIN - score - moves) as usize,
+        },
+        score => Value::Loss {
+            plies: (WIN + score - moves) as usize,
+        },
+    };
+    Ok(Solution {
+        value,
+        best_move: Some(col),
+    })
+}
+
+/// Plays `solve`'s best move over and over from `state` and returns the columns played. With
+/// perfect play from both sides that's how the game goes, as long as its `Solution`.
+pub fn principal_variation(state: &Connect4State) -> Result<Vec<usize>, BitBoardError> {
+    let mut state = state.clone();
+    let mut line = vec![];
+    while let Some(column) = solve(&state)?.best_move {
+        line.push(column);
+        apply_action(&mut state, &Connect4Action::Drop { column })
+            .expect("the solver only picks columns that aren't full");
+    }
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{check_state, Connect4Check, Connect4Result};
+
+    #[test]
+    fn immediate_win() {
+        let state = Connect4State::from_moves("112233").unwrap();
+        let solution = solve(&state).unwrap();
+        assert_eq!(solution.value, Value::Win { plies: 1 });
+        assert_eq!(solution.best_move, Some(3));
+    }
+
+    #[test]
+    fn forced_win_with_a_fork() {
+        // Extending the two in the bottom row to an open three wins two moves later.
+        let state = Connect4State::from_moves("3747").unwrap();
+        let solution = solve(&state).unwrap();
+        assert_eq!(solution.value, Value::Win { plies: 3 });
+        assert!(matches!(solution.best_move, Some(1 | 4)));
+    }
+
+    #[test]
+    fn lost_position() {
+        // Player 1 to move can only block one end of the open three.
+        let state = Connect4State::from_moves("37475").unwrap();
+        let solution = solve(&state).unwrap();
+        assert_eq!(solution.value, Value::Loss { plies: 2 });
+    }
+
+    #[test]
+    fn playing_the_best_move_keeps_the_value() {
+        // A position from the middle of a real game, with 16 discs down.
+        let mut state = Connect4State::from_moves("4444335235522211").unwrap();
+        let mut solution = solve(&state).unwrap();
+        while let Some(column) = solution.best_move {
+            let check = apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+            let next = solve(&state).unwrap();
+            // The value flips to the other player and gets one move closer.
+            let expected = match solution.value {
+                Value::Win { plies } => Value::Loss { plies: plies - 1 },
+                Value::Loss { plies } => Value::Win { plies: plies - 1 },
+                Value::Draw => Value::Draw,
+            };
+            if let Connect4Check::Over(result) = check {
+                assert_eq!(check_state(&state), Connect4Check::Over(result.clone()));
+                match result {
+                    Connect4Result::Winner { .. } => {
+                        assert_eq!(next.value, Value::Loss { plies: 0 })
+                    }
+                    Connect4Result::Tie => assert_eq!(next.value, Value::Draw),
+                }
+                break;
+            }
+            assert_eq!(next.value, expected);
+            solution = next;
+        }
+    }
+
+    #[test]
+    fn mirrored_positions_solve_the_same() {
+        let state = Connect4State::from_moves("4444335235522211").unwrap();
+        let solution = solve(&state).unwrap();
+        let mirrored = solve(&crate::mirror(&state)).unwrap();
+        assert_eq!(mirrored.value, solution.value);
+    }
+
+    #[test]
+    fn principal_variation_is_as_long_as_the_win() {
+        let state = Connect4State::from_moves("3747").unwrap();
+        let line = principal_variation(&state).unwrap();
+        assert_eq!(line.len(), 3);
+        let mut end = state.clone();
+        for column in line {
+            apply_action(&mut end, &Connect4Action::Drop { column }).unwrap();
+        }
+        assert!(matches!(
+            check_state(&end),
+            Connect4Check::Over(Connect4Result::Winner { player: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn only_standard_boards() {
+        let state = Connect4State::new(crate::BoardConfig::new(5, 5, 4).unwrap());
+        assert!(solve(&state).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    apply_action, legal_moves, undo_action, Connect4Action, Connect4Check, Connect4Result,
+    Connect4State,
+};
+
+/// Columns where `player` would complete a line if they dropped a disc there on their next move,
+/// whether or not it's their turn now.
+pub fn immediate_threats(state: &Connect4State, player: usize) -> Vec<usize> {
+    let mut state = state.clone();
+    state.next_player = player;
+    legal_moves(&state)
+        .into_iter()
+        .filter(|&column| {
+            let action = Connect4Action::Drop { column };
+            let check = apply_action(&mut state, &action).unwrap();
+            undo_action(&mut state, &action).unwrap();
+            match check {
+                Connect4Check::Over(Connect4Result::Winner { player: winner, .. }) => {
+                    winner == player
+                }
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+/// A drop that gives the player to move two immediate threats at once, when no opponent has a
+/// win of their own to play first. They can only block one of them, so the position is lost for
+/// the opponent. Moves that win straight away aren't counted, look for those first with
+/// `immediate_threats`.
+pub fn forking_move(state: &Connect4State) -> Option<usize> {
+    let player = state.next_player;
+    let mut state = state.clone();
+    legal_moves(&state).into_iter().find(|&column| {
+        let action = Connect4Action::Drop { column };
+        let check = apply_action(&mut state, &action).unwrap();
+        let fork = check == Connect4Check::InProgress
+            && (0..state.config.players)
+                .filter(|&opponent| opponent != player)
+                .all(|opponent| immediate_threats(&state, opponent).is_empty())
+            && immediate_threats(&state, player).len() >= 2;
+        undo_action(&mut state, &action).unwrap();
+        fork
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_threat() {
+        // Player 1 has three along the bottom, only column 4 (1-indexed) finishes it. It's player 0's
+        // turn but the threat is still there.
+        let state = Connect4State::from_moves("717263").unwrap();
+        assert_eq!(immediate_threats(&state, 1), [3]);
+        assert!(immediate_threats(&state, 0).is_empty());
+    }
+
+    #[test]
+    fn double_threat() {
+        // Player 0 has columns 3 to 5 (1-indexed) of the bottom row with both ends open.
+        let state = Connect4State::from_moves("37475").unwrap();
+        assert_eq!(immediate_threats(&state, 0), [1, 5]);
+        assert!(immediate_threats(&state, 1).is_empty());
+    }
+
+    #[test]
+    fn fork() {
+        // Player 0 has columns 3 and 4 (1-indexed) of the bottom row. Dropping in column 2 makes
+        // three with both ends open.
+        let state = Connect4State::from_moves("3747").unwrap();
+        assert_eq!(forking_move(&state), Some(1));
+        assert_eq!(forking_move(&Connect4State::default()), None);
+    }
+
+    #[test]
+    fn no_fork_when_the_opponent_wins_first() {
+        // Column 5 gives player 0 two threats, but player 1 can finish column 1 first.
+        let mut state = Connect4State::from_moves("314171").unwrap();
+        apply_action(&mut state, &Connect4Action::Drop { column: 4 }).unwrap();
+        assert_eq!(immediate_threats(&state, 0).len(), 2);
+        undo_action(&mut state, &Connect4Action::Drop { column: 4 }).unwrap();
+        assert_eq!(forking_move(&state), None);
+    }
+}
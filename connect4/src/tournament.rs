@@ -0,0 +1,182 @@
+use std::{
+    cmp, fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rayon::prelude::*;
+
+use crate::{play, Agent, BoardConfig, Connect4Result, Connect4State};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TournamentResult {
+    /// The agents' `Agent::name`s.
+    pub a_name: String,
+    pub b_name: String,
+    pub a_wins: usize,
+    pub b_wins: usize,
+    pub ties: usize,
+}
+
+impl TournamentResult {
+    pub fn games(&self) -> usize {
+        self.a_wins + self.b_wins + self.ties
+    }
+
+    // Adds up the games from two parts of the tournament. Parts without any games don't know the
+    // names.
+    fn merge(self, other: Self) -> Self {
+        let (a_name, b_name) = if self.a_name.is_empty() {
+            (other.a_name, other.b_name)
+        } else {
+            (self.a_name, self.b_name)
+        };
+        Self {
+            a_name,
+            b_name,
+            a_wins: self.a_wins + other.a_wins,
+            b_wins: self.b_wins + other.b_wins,
+            ties: self.ties + other.ties,
+        }
+    }
+}
+
+/// Says who won with the score, like `MCTS(1000) beat GreedyAgent 15-4 with 1 tie`.
+impl fmt::Display for TournamentResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ties = match self.ties {
+            1 => "1 tie".to_string(),
+            ties => format!("{} ties", ties),
+        };
+        match self.a_wins.cmp(&self.b_wins) {
+            cmp::Ordering::Less => write!(
+                f,
+                "{} beat {} {}-{} with {}",
+                self.b_name, self.a_name, self.b_wins, self.a_wins, ties
+            ),
+            cmp::Ordering::Equal => write!(
+                f,
+                "{} and {} drew {}-{} with {}",
+                self.a_name, self.b_name, self.a_wins, self.b_wins, ties
+            ),
+            cmp::Ordering::Greater => write!(
+                f,
+                "{} beat {} {}-{} with {}",
+                self.a_name, self.b_name, self.a_wins, self.b_wins, ties
+            ),
+        }
+    }
+}
+
+/// Plays `games` games between two agents in parallel. Agent A is always player 0 and agent B
+/// player 1, A moves first in the even numbered games and B in the odd ones so neither gets more
+/// of the first move advantage.
+///
+/// Agents can't be shared between threads so each game builds its own with `make_a(i)` and
+/// `make_b(i)`, where `i` is the game number. That's also the place to seed them.
+pub fn run_tournament<A, B>(
+    make_a: impl Fn(usize) -> A + Sync,
+    make_b: impl Fn(usize) -> B + Sync,
+    games: usize,
+) -> TournamentResult
+where
+    A: Agent,
+    B: Agent,
+{
+    run_tournament_with_progress(make_a, make_b, games, |_, _| {})
+}
+
+/// `run_tournament` that calls `progress(finished, games)` every time a game finishes. Games
+/// finish on different threads in whatever order they happen to, but `finished` counts up by one
+/// each call.
+pub fn run_tournament_with_progress<A, B>(
+    make_a: impl Fn(usize) -> A + Sync,
+    make_b: impl Fn(usize) -> B + Sync,
+    games: usize,
+    progress: impl Fn(usize, usize) + Sync,
+) -> TournamentResult
+where
+    A: Agent,
+    B: Agent,
+{
+    let finished = AtomicUsize::new(0);
+    (0..games)
+        .into_par_iter()
+        .map(|i| {
+            let mut a = make_a(i);
+            let mut b = make_b(i);
+            let mut state = Connect4State::with_first_player(BoardConfig::default(), i % 2);
+            let result = play(&mut state, &mut a, &mut b).unwrap().result;
+            let mut tally = TournamentResult {
+                a_name: a.name().to_string(),
+                b_name: b.name().to_string(),
+                ..Default::default()
+            };
+            match result {
+                Connect4Result::Winner { player: 0, .. } => tally.a_wins += 1,
+                Connect4Result::Winner { .. } => tally.b_wins += 1,
+                Connect4Result::Tie => tally.ties += 1,
+            }
+            progress(finished.fetch_add(1, Ordering::Relaxed) + 1, games);
+            tally
+        })
+        .reduce(TournamentResult::default, TournamentResult::merge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use crate::{greedy::GreedyAgent, RandomAgent};
+
+    #[test]
+    fn greedy_beats_random() {
+        let result = run_tournament(|_| GreedyAgent, |i| RandomAgent::seeded(i as u64), 20);
+        assert_eq!(result.games(), 20);
+        assert!(result.a_wins > result.b_wins);
+        assert_eq!(
+            (result.a_name.as_str(), result.b_name.as_str()),
+            ("GreedyAgent", "RandomAgent")
+        );
+    }
+
+    #[test]
+    fn reports_the_winner_by_name() {
+        let result = run_tournament(
+            |i| RandomAgent::seeded(i as u64).named("Random"),
+            |_| GreedyAgent,
+            10,
+        );
+        let report = result.to_string();
+        assert!(report.starts_with("GreedyAgent beat Random "), "{}", report);
+
+        let result = TournamentResult {
+            a_name: "A".to_string(),
+            b_name: "B".to_string(),
+            a_wins: 2,
+            b_wins: 2,
+            ties: 1,
+        };
+        assert_eq!(result.to_string(), "A and B drew 2-2 with 1 tie");
+    }
+
+    #[test]
+    fn progress_counts_every_game() {
+        let calls = Mutex::new(vec![]);
+        let make_b = |i: usize| RandomAgent::seeded(i as u64);
+        let result = run_tournament_with_progress(
+            |i| RandomAgent::seeded(1000 + i as u64),
+            make_b,
+            50,
+            |finished, games| calls.lock().unwrap().push((finished, games)),
+        );
+        let mut calls = calls.into_inner().unwrap();
+        calls.sort();
+        assert_eq!(calls, (1..=50).map(|i| (i, 50)).collect::<Vec<_>>());
+        // Seeding from the game number makes the whole tournament reproducible.
+        assert_eq!(
+            result,
+            run_tournament(|i| RandomAgent::seeded(1000 + i as u64), make_b, 50)
+        );
+    }
+}
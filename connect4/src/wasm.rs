@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{apply_action, Agent, Connect4Action, Connect4Check, Connect4State, MctsAgent};
+
+// What `play_move` and `bot_move` send back, as JSON.
+#[derive(Serialize)]
+struct Moved {
+    action: Connect4Action,
+    state: Connect4State,
+    check: Connect4Check,
+}
+
+fn moved(mut state: Connect4State, action: Connect4Action) -> Result<String, JsError> {
+    let check = apply_action(&mut state, &action)?;
+    Ok(serde_json::to_string(&Moved {
+        action,
+        state,
+        check,
+    })?)
+}
+
+/// A new game on the standard board, as JSON. Every other function takes the state in this form.
+#[wasm_bindgen]
+pub fn new_game() -> String {
+    serde_json::to_string(&Connect4State::default()).unwrap()
+}
+
+/// Drops a disc in `column` for the player to move. Returns JSON with the `action`, the new
+/// `state` and the `check` of whether the game is over.
+#[wasm_bindgen]
+pub fn play_move(state: &str, column: usize) -> Result<String, JsError> {
+    let state: Connect4State = serde_json::from_str(state)?;
+    moved(state, Connect4Action::Drop { column })
+}
+
+/// Lets MCTS think for `think_ms` milliseconds and plays its move. Returns the same JSON as
+/// `play_move`.
+#[wasm_bindgen]
+pub fn bot_move(state: &str, think_ms: u32) -> Result<String, JsError> {
+    let state: Connect4State = serde_json::from_str(state)?;
+    let mut bot = MctsAgent::with_time_budget(Duration::from_millis(think_ms.into()));
+    let action = bot
+        .choose(&state)
+        .ok_or_else(|| JsError::new("The game is over."))?;
+    moved(state, action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn human_then_bot() {
+        let json = play_move(&new_game(), 3).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["check"], "InProgress");
+        let state = serde_json::to_string(&value["state"]).unwrap();
+
+        let json = bot_move(&state, 10).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let state: Connect4State = serde_json::from_value(value["state"].clone()).unwrap();
+        assert_eq!(state.history.len(), 2);
+        assert_eq!(state.next_player, 0);
+    }
+}
@@ -0,0 +1,145 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{mirror, BoardConfig, Connect4State};
+
+/// Zobrist hashing: every (cell, player) pair gets a random key and a position hashes to the xor
+/// of the keys of its discs, plus one more key for the player to move unless it's the first.
+///
+/// Because xor is its own inverse the hash can be updated with a single xor per move, both when
+/// making and when unmaking it. Identical positions always hash the same no matter what order the
+/// moves were played in.
+pub struct ZobristHasher {
+    config: BoardConfig,
+    // Indexed like `Connect4State::board`, one key per player.
+    keys: Vec<Vec<u64>>,
+    // One per player to move, 0 for player 0.
+    sides: Vec<u64>,
+}
+
+impl ZobristHasher {
+    /// Uses a fixed seed so hashes are the same from run to run.
+    pub fn new(config: BoardConfig) -> Self {
+        Self::seeded(config, 0)
+    }
+
+    pub fn seeded(config: BoardConfig, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let keys = (0..config.rows * config.cols)
+            .map(|_| (0..config.players).map(|_| rng.gen()).collect())
+            .collect();
+        let sides = (0..config.players)
+            .map(|player| if player == 0 { 0 } else { rng.gen() })
+            .collect();
+        Self {
+            config,
+            keys,
+            sides,
+        }
+    }
+
+    /// Hashes a whole position from scratch.
+    pub fn hash(&self, state: &Connect4State) -> u64 {
+        debug_assert_eq!(state.config, self.config);
+        let mut hash = self.sides[state.next_player];
+        for (cell, keys) in state.board.iter().zip(&self.keys) {
+            if let Some(player) = cell {
+                hash ^= keys[*player];
+            }
+        }
+        hash
+    }
+
+    /// The smaller of the hashes of `state` and its `mirror`, so a position and its mirror share a
+    /// key. Use it for tables whose entries don't depend on which way round the board is.
+    pub fn canonical(&self, state: &Connect4State) -> u64 {
+        self.hash(state).min(self.hash(&mirror(state)))
+    }
+
+    /// Adds or removes `player`'s disc at (col, row) and switches the side to move between
+    /// `player` and the player after them.
+    pub fn toggle(&self, hash: u64, col: usize, row: usize, player: usize) -> u64 {
+        let next = self.config.player_after(player);
+        hash ^ self.keys[col * self.config.rows + row][player]
+            ^ self.sides[player]
+            ^ self.sides[next]
+    }
+
+    /// Updates `hash` for the top disc of `column`. Call it right after `apply_action` or right
+    /// before `undo_action` of a drop, when that disc is on the board in both cases. A pop out
+    /// moves every disc in its column, rehash those positions with `hash`.
+    pub fn update(&self, hash: u64, state: &Connect4State, column: usize) -> u64 {
+        let row = (0..self.config.rows)
+            .rev()
+            .find(|&row| state.cell(column, row).is_some())
+            .expect("column has no discs");
+        let player = state.cell(column, row).unwrap();
+        self.toggle(hash, column, row, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply_action, undo_action, Connect4Action};
+
+    #[test]
+    fn incremental_matches_full_hash() {
+        let hasher = ZobristHasher::new(BoardConfig::default());
+        let mut state = Connect4State::default();
+        let mut hash = hasher.hash(&state);
+        let mut hashes = vec![hash];
+        for column in [3, 3, 2, 4, 4, 0, 6] {
+            apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+            hash = hasher.update(hash, &state, column);
+            assert_eq!(hash, hasher.hash(&state));
+            hashes.push(hash);
+        }
+        hashes.pop();
+        while let Some(&action) = state.history.last() {
+            hash = hasher.update(hash, &state, action.column());
+            undo_action(&mut state, &action).unwrap();
+            assert_eq!(hash, hashes.pop().unwrap());
+        }
+    }
+
+    #[test]
+    fn incremental_matches_full_hash_with_three_players() {
+        let config = BoardConfig::default().with_players(3).unwrap();
+        let hasher = ZobristHasher::new(config);
+        let mut state = Connect4State::new(config);
+        let mut hash = hasher.hash(&state);
+        for column in [3, 3, 2, 4, 4, 0, 6] {
+            apply_action(&mut state, &Connect4Action::Drop { column }).unwrap();
+            hash = hasher.update(hash, &state, column);
+            assert_eq!(hash, hasher.hash(&state));
+        }
+    }
+
+    #[test]
+    fn transpositions_hash_equal() {
+        let hasher = ZobristHasher::new(BoardConfig::default());
+        let a = Connect4State::from_moves("1234").unwrap();
+        let b = Connect4State::from_moves("3214").unwrap();
+        let c = Connect4State::from_moves("2143").unwrap();
+        assert_eq!(hasher.hash(&a), hasher.hash(&b));
+        assert_ne!(hasher.hash(&a), hasher.hash(&c));
+    }
+
+    #[test]
+    fn mirrors_share_a_canonical_key() {
+        let hasher = ZobristHasher::new(BoardConfig::default());
+        let state = Connect4State::from_moves("1123").unwrap();
+        let mirrored = mirror(&state);
+        assert_eq!(mirrored.to_moves(), "7765");
+        assert_eq!(
+            mirrored.board,
+            Connect4State::from_moves("7765").unwrap().board
+        );
+        assert_ne!(hasher.hash(&state), hasher.hash(&mirrored));
+        assert_eq!(hasher.canonical(&state), hasher.canonical(&mirrored));
+        assert_ne!(
+            hasher.canonical(&state),
+            hasher.canonical(&Connect4State::from_moves("1124").unwrap())
+        );
+    }
+}
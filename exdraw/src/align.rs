@@ -0,0 +1,344 @@
+use crate::{BoundElementKind, BoundingBox, Element, ElementKind, ExcalidrawFile};
+
+/// Which side or center of the elements `align` lines up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    /// Centers the elements horizontally, on a vertical line.
+    CenterX,
+    Right,
+    Top,
+    /// Centers the elements vertically, on a horizontal line.
+    MiddleY,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    // Where a box starts and ends along the axis.
+    fn extent(self, bounds: &BoundingBox) -> (f64, f64) {
+        match self {
+            Axis::Horizontal => (bounds.min_x, bounds.max_x),
+            Axis::Vertical => (bounds.min_y, bounds.max_y),
+        }
+    }
+
+    fn offset(self, distance: f64) -> (f64, f64) {
+        match self {
+            Axis::Horizontal => (distance, 0.),
+            Axis::Vertical => (0., distance),
+        }
+    }
+}
+
+// The bounding boxes of the elements with these ids, `None` if one isn't in the file.
+fn bounds(file: &ExcalidrawFile, ids: &[&str]) -> Option<Vec<BoundingBox>> {
+    ids.iter()
+        .map(|id| Some(file.element(id)?.bounding_box()))
+        .collect()
+}
+
+// Moves the labels inside the element with this id after it's moved by `dx` and `dy`, unless
+// they're in `ids` and being moved already.
+fn move_labels(file: &mut ExcalidrawFile, ids: &[&str], id: &str, dx: f64, dy: f64) {
+    let Some(element) = file.element(id) else {
+        return;
+    };
+    let labels: Vec<_> = element
+        .bound_elements
+        .iter()
+        .filter(|bound| bound.r#type == BoundElementKind::Text && !ids.contains(&bound.id.as_str()))
+        .map(|bound| bound.id.clone())
+        .collect();
+    for label in labels {
+        if let Some(label) = file.element_mut(&label) {
+            label.translate(dx, dy);
+        }
+    }
+}
+
+/// Moves the elements with these ids so they line up, the same as Excalidraw's align buttons.
+/// They line up with the edge or center of the box around all of them, so aligning left moves
+/// everything to the leftmost element. Elements are lined up by their bounding boxes, which for
+/// shapes is their `x`, `y`, `width` and `height`. Labels move with their shapes, but arrows bound
+/// to them aren't redrawn. Returns `None` without changing anything if one of the ids isn't in
+/// the file.
+pub fn align(file: &mut ExcalidrawFile, ids: &[&str], alignment: Alignment) -> Option<()> {
+    let bounds = bounds(file, ids)?;
+    let Some(all) = bounds.iter().copied().reduce(|a, b| a.union(&b)) else {
+        return Some(());
+    };
+    let [center_x, center_y] = all.center();
+    for (id, bounds) in ids.iter().zip(bounds) {
+        let [x, y] = bounds.center();
+        let (dx, dy) = match alignment {
+            Alignment::Left => (all.min_x - bounds.min_x, 0.),
+            Alignment::CenterX => (center_x - x, 0.),
+            Alignment::Right => (all.max_x - bounds.max_x, 0.),
+            Alignment::Top => (0., all.min_y - bounds.min_y),
+            Alignment::MiddleY => (0., center_y - y),
+            Alignment::Bottom => (0., all.max_y - bounds.max_y),
+        };
+        file.element_mut(id)?.translate(dx, dy);
+        move_labels(file, ids, id, dx, dy);
+    }
+    Some(())
+}
+
+/// Spaces the elements with these ids evenly along `axis`, so there's the same gap between each
+/// one and the next, in the order of their centers. The space they cover stays the same. Labels
+/// move with their shapes like they do in `align`. Returns `None` without changing anything if
+/// one of the ids isn't in the file.
+pub fn distribute(file: &mut ExcalidrawFile, ids: &[&str], axis: Axis) -> Option<()> {
+    let bounds = bounds(file, ids)?;
+    if ids.len() < 3 {
+        return Some(());
+    }
+    let mut order: Vec<_> = (0..ids.len()).collect();
+    order.sort_by(|&a, &b| {
+        let center = |i: usize| {
+            let (start, end) = axis.extent(&bounds[i]);
+            (start + end) / 2.
+        };
+        center(a).total_cmp(&center(b))
+    });
+    let start = bounds
+        .iter()
+        .map(|bounds| axis.extent(bounds).0)
+        .fold(f64::INFINITY, f64::min);
+    let end = bounds
+        .iter()
+        .map(|bounds| axis.extent(bounds).1)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let lengths: f64 = bounds
+        .iter()
+        .map(|bounds| {
+            let (start, end) = axis.extent(bounds);
+            end - start
+        })
+        .sum();
+    let gap = (end - start - lengths) / (ids.len() - 1) as f64;
+
+    let mut next = start;
+    for i in order {
+        let (element_start, element_end) = axis.extent(&bounds[i]);
+        let (dx, dy) = axis.offset(next - element_start);
+        file.element_mut(ids[i])?.translate(dx, dy);
+        move_labels(file, ids, ids[i], dx, dy);
+        next += element_end - element_start + gap;
+    }
+    Some(())
+}
+
+/// Mirrors the elements with these ids left to right, around the center of the box around all of
+/// them. Lines and arrows get their points mirrored, images are drawn flipped and rotations go
+/// the other way, but text is just moved, it'd be unreadable backwards. Labels move with their
+/// shapes like they do in `align`. Flipping twice puts everything back. Returns `None` without
+/// changing anything if one of the ids isn't in the file.
+pub fn flip_horizontal(file: &mut ExcalidrawFile, ids: &[&str]) -> Option<()> {
+    flip(file, ids, Axis::Horizontal)
+}
+
+/// Mirrors the elements with these ids top to bottom, see `flip_horizontal`.
+pub fn flip_vertical(file: &mut ExcalidrawFile, ids: &[&str]) -> Option<()> {
+    flip(file, ids, Axis::Vertical)
+}
+
+fn flip(file: &mut ExcalidrawFile, ids: &[&str], axis: Axis) -> Option<()> {
+    let Some(all) = bounds(file, ids)?.into_iter().reduce(|a, b| a.union(&b)) else {
+        return Some(());
+    };
+    let (start, end) = axis.extent(&all);
+    let center = (start + end) / 2.;
+    for id in ids {
+        let element = file.element_mut(id)?;
+        let [x0, y0] = element.bounding_box().center();
+        flip_element(element, axis, center);
+        // Labels aren't flipped with the shape, just moved along with it.
+        let [x1, y1] = element.bounding_box().center();
+        move_labels(file, ids, id, x1 - x0, y1 - y0);
+    }
+    Some(())
+}
+
+// Mirrors the element across the line at `center` along `axis`.
+fn flip_element(element: &mut Element, axis: Axis, center: f64) {
+    let (position, size, i) = match axis {
+        Axis::Horizontal => (&mut element.x, element.width, 0),
+        Axis::Vertical => (&mut element.y, element.height, 1),
+    };
+    match &mut element.kind {
+        // The position is the first point and the rest are relative to it.
+        ElementKind::Line { points } | ElementKind::Arrow { points, .. } => {
+            *position = 2. * center - *position;
+            for point in points {
+                point[i] = -point[i];
+            }
+        }
+        kind => {
+            *position = 2. * center - *position - size;
+            if let ElementKind::Image { scale, .. } = kind {
+                scale[i] = -scale[i];
+            }
+        }
+    }
+    element.angle = -element.angle;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout_flowchart, seed_ids, Line, Node, Rectangle, Text};
+
+    // Seeded so two scenes are the same down to their elements' seeds.
+    fn scene() -> ExcalidrawFile {
+        seed_ids(0);
+        ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(150., 100., 20., 10.).id("b").build(),
+                Line::new(&[[60., 300.], [20., 320.]]).id("c").build(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn align_each_way() {
+        let ids = ["a", "b", "c"];
+        let check = |alignment, expected: [f64; 3]| {
+            let mut file = scene();
+            align(&mut file, &ids, alignment).unwrap();
+            let edges: Vec<_> = ids
+                .iter()
+                .map(|id| {
+                    let bounds = file.element(id).unwrap().bounding_box();
+                    match alignment {
+                        Alignment::Left => bounds.min_x,
+                        Alignment::CenterX => bounds.center()[0],
+                        Alignment::Right => bounds.max_x,
+                        Alignment::Top => bounds.min_y,
+                        Alignment::MiddleY => bounds.center()[1],
+                        Alignment::Bottom => bounds.max_y,
+                    }
+                })
+                .collect();
+            assert_eq!(edges, expected);
+        };
+        check(Alignment::Left, [0.; 3]);
+        check(Alignment::CenterX, [85.; 3]);
+        check(Alignment::Right, [170.; 3]);
+        check(Alignment::Top, [0.; 3]);
+        check(Alignment::MiddleY, [160.; 3]);
+        check(Alignment::Bottom, [320.; 3]);
+
+        // Only one coordinate changes.
+        let mut file = scene();
+        align(&mut file, &ids, Alignment::Left).unwrap();
+        assert_eq!(file.element("b").unwrap().y, 100.);
+    }
+
+    #[test]
+    fn distribute_evenly() {
+        let mut file = scene();
+        distribute(&mut file, &["c", "a", "b"], Axis::Vertical).unwrap();
+        let a = file.element("a").unwrap().bounding_box();
+        let b = file.element("b").unwrap().bounding_box();
+        let c = file.element("c").unwrap().bounding_box();
+        assert_eq!((a.min_y, c.max_y), (0., 320.));
+        assert_eq!(b.min_y - a.max_y, c.min_y - b.max_y);
+        assert_eq!(b.min_y - a.max_y, 120.);
+        // The other axis is left alone.
+        assert_eq!(b.min_x, 150.);
+    }
+
+    #[test]
+    fn flip_and_back() {
+        let mut file = scene();
+        file.elements
+            .push(Text::new(60., 100., "hi").id("t").rotate(0.5).build());
+        let image = ElementKind::Image {
+            file_id: "png".into(),
+            status: "saved".into(),
+            scale: [1., 1.],
+        };
+        file.elements
+            .push(Element::new("i", image, 0., 0., 10., 10.));
+        let ids = ["a", "b", "c", "t", "i"];
+
+        flip_horizontal(&mut file, &ids).unwrap();
+        assert_eq!(file.element("a").unwrap().x, 70.);
+        assert_eq!(file.element("b").unwrap().x, 0.);
+        let c = file.element("c").unwrap();
+        assert_eq!((c.x, c.y), (110., 300.));
+        assert_eq!(
+            c.kind,
+            ElementKind::Line {
+                points: vec![[0., 0.], [40., 20.]]
+            }
+        );
+        assert_eq!(c.bounding_box().min_x, 110.);
+        assert_eq!(file.element("t").unwrap().angle, -0.5);
+        let ElementKind::Image { scale, .. } = &file.element("i").unwrap().kind else {
+            panic!("not an image");
+        };
+        assert_eq!(*scale, [-1., 1.]);
+
+        flip_horizontal(&mut file, &ids).unwrap();
+        flip_vertical(&mut file, &ids).unwrap();
+        assert_eq!(file.element("a").unwrap().y, 270.);
+        flip_vertical(&mut file, &ids).unwrap();
+        let mut original = scene();
+        original
+            .elements
+            .push(Text::new(60., 100., "hi").id("t").rotate(0.5).build());
+        assert_eq!(file.elements[..4], original.elements[..]);
+        let ElementKind::Image { scale, .. } = &file.element("i").unwrap().kind else {
+            panic!("not an image");
+        };
+        assert_eq!(*scale, [1., 1.]);
+    }
+
+    #[test]
+    fn labels_move_with_their_shapes() {
+        let mut file = layout_flowchart(&[Node::new("a"), Node::new("b")], &[(0, 1)]);
+        let label = file.element("node-0").unwrap().bound_elements[0].id.clone();
+        for id in ["node-0", &label] {
+            file.element_mut(id).unwrap().x -= 300.;
+        }
+        let offset = |file: &ExcalidrawFile| {
+            let [x0, y0] = file.element("node-0").unwrap().bounding_box().center();
+            let [x1, y1] = file.element(&label).unwrap().bounding_box().center();
+            (x1 - x0, y1 - y0)
+        };
+        let inside = offset(&file);
+        let before = file.element("node-0").unwrap().x;
+        flip_horizontal(&mut file, &["node-0", "node-1"]).unwrap();
+        assert_ne!(file.element("node-0").unwrap().x, before);
+        assert_eq!(offset(&file), inside);
+        align(&mut file, &["node-0", "node-1"], Alignment::Left).unwrap();
+        assert_eq!(offset(&file), inside);
+
+        // Listing the label too doesn't move it twice.
+        for id in ["node-0", &label] {
+            file.element_mut(id).unwrap().x -= 300.;
+        }
+        align(&mut file, &["node-0", &label, "node-1"], Alignment::Right).unwrap();
+        let right = |id: &str| file.element(id).unwrap().bounding_box().max_x;
+        assert_eq!(right(&label), right("node-1"));
+    }
+
+    #[test]
+    fn missing_ids() {
+        let mut file = scene();
+        assert_eq!(align(&mut file, &["a", "nope"], Alignment::Top), None);
+        assert_eq!(distribute(&mut file, &["a", "nope"], Axis::Vertical), None);
+        assert_eq!(flip_vertical(&mut file, &["a", "nope"]), None);
+        assert_eq!(file, scene());
+    }
+}
@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Arrow, Element, ElementKind, ExcalidrawFile};
+
+/// Space left between a bound arrow and the shape it's attached to.
+const GAP: f64 = 8.;
+
+/// One end of an arrow attached to a shape. Excalidraw keeps the arrow attached when the shape
+/// moves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Binding {
+    pub element_id: String,
+    /// Where the arrow aims, from -1 to 1 across the shape. 0 is straight at the center.
+    pub focus: f64,
+    /// Distance between the end of the arrow and the shape.
+    pub gap: f64,
+}
+
+/// The shape's side of a binding, listed in its `boundElements`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BoundElement {
+    pub id: String,
+    pub r#type: BoundElementKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BoundElementKind {
+    Arrow,
+    /// Text inside a shape.
+    Text,
+}
+
+// How far along `direction` from the center of `element` its bounding box ends, as a multiple of
+// `direction`.
+fn edge(element: &Element, direction: [f64; 2]) -> f64 {
+    let along = |half: f64, d: f64| {
+        if d == 0. {
+            f64::INFINITY
+        } else {
+            half / d.abs()
+        }
+    };
+    let t = along(element.width / 2., direction[0]).min(along(element.height / 2., direction[1]));
+    if t.is_finite() {
+        t
+    } else {
+        0.
+    }
+}
+
+/// Adds an arrow from the element `from` to the element `to`, bound at both ends. It runs
+/// between the centers of the two shapes and stops just short of their edges. Returns the
+/// arrow's id, or `None` if either element isn't in the file.
+pub fn bind_arrow(file: &mut ExcalidrawFile, from: &str, to: &str) -> Option<String> {
+    let start_element = file.element(from)?;
+    let end_element = file.element(to)?;
+
+    let [x0, y0] = start_element.bounding_box().center();
+    let [x1, y1] = end_element.bounding_box().center();
+    let direction = [x1 - x0, y1 - y0];
+    let length = direction[0].hypot(direction[1]);
+    let (ux, uy) = if length == 0. {
+        (0., 0.)
+    } else {
+        (direction[0] / length, direction[1] / length)
+    };
+    let t0 = edge(start_element, direction);
+    let t1 = edge(end_element, direction);
+    let start = [
+        x0 + direction[0] * t0 + ux * GAP,
+        y0 + direction[1] * t0 + uy * GAP,
+    ];
+    let end = [
+        x1 - direction[0] * t1 - ux * GAP,
+        y1 - direction[1] * t1 - uy * GAP,
+    ];
+
+    Some(bind(file, from, to, Arrow::new(start, end).build()))
+}
+
+/// Like `bind_arrow` but the arrow only runs horizontally and vertically. It leaves the side of
+/// `from` facing `to`, turns halfway between them and comes into the facing side of `to`, so it
+/// has three segments unless the shapes are lined up. It has sharp corners, Excalidraw draws
+/// round arrows as curves. Shapes further apart across than down are joined side to side, the
+/// others top to bottom.
+pub fn elbow_arrow(file: &mut ExcalidrawFile, from: &str, to: &str) -> Option<String> {
+    let start_element = file.element(from)?;
+    let end_element = file.element(to)?;
+
+    let [x0, y0] = start_element.bounding_box().center();
+    let [x1, y1] = end_element.bounding_box().center();
+    // The arrow is worked out as if it goes across, and flipped for arrows that go down.
+    let across = (x1 - x0).abs() >= (y1 - y0).abs();
+    let flip = |[a, b]: [f64; 2]| if across { [a, b] } else { [b, a] };
+    let ([a0, b0], [a1, b1]) = (flip([x0, y0]), flip([x1, y1]));
+    let [half0, _] = flip([start_element.width / 2., start_element.height / 2.]);
+    let [half1, _] = flip([end_element.width / 2., end_element.height / 2.]);
+    let sign = if a1 < a0 { -1. } else { 1. };
+    let start = a0 + sign * (half0 + GAP);
+    let end = a1 - sign * (half1 + GAP);
+    let points = if b0 == b1 {
+        vec![[start, b0], [end, b1]]
+    } else {
+        let middle = (start + end) / 2.;
+        vec![[start, b0], [middle, b0], [middle, b1], [end, b1]]
+    };
+    let points: Vec<_> = points.into_iter().map(flip).collect();
+    Some(bind(
+        file,
+        from,
+        to,
+        Arrow::through(&points).round(false).build(),
+    ))
+}
+
+// Adds `arrow` bound to `from` at the start and `to` at the end, both of which are in the file.
+fn bind(file: &mut ExcalidrawFile, from: &str, to: &str, mut arrow: Element) -> String {
+    let binding = |element_id: &str| {
+        Some(Binding {
+            element_id: element_id.to_string(),
+            focus: 0.,
+            gap: GAP,
+        })
+    };
+    if let ElementKind::Arrow {
+        start_binding,
+        end_binding,
+        ..
+    } = &mut arrow.kind
+    {
+        *start_binding = binding(from);
+        *end_binding = binding(to);
+    }
+    let id = arrow.id.clone();
+    file.elements.push(arrow);
+
+    for shape in [from, to] {
+        let bound = &mut file.element_mut(shape).unwrap().bound_elements;
+        // An arrow from a shape to itself is only listed once.
+        if !bound.iter().any(|bound| bound.id == id) {
+            bound.push(BoundElement {
+                id: id.clone(),
+                r#type: BoundElementKind::Arrow,
+            });
+        }
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rectangle;
+
+    #[test]
+    fn binds_both_ends() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(300., 0., 100., 50.).id("b").build(),
+            ],
+            ..Default::default()
+        };
+        let id = bind_arrow(&mut file, "a", "b").unwrap();
+
+        let arrow = file.element(&id).unwrap();
+        assert_eq!((arrow.x, arrow.y), (100. + GAP, 25.));
+        assert_eq!(arrow.width, 200. - 2. * GAP);
+        let ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } = &arrow.kind
+        else {
+            panic!("not an arrow");
+        };
+        assert_eq!(start_binding.as_ref().unwrap().element_id, "a");
+        assert_eq!(end_binding.as_ref().unwrap().element_id, "b");
+
+        for shape in ["a", "b"] {
+            let bound = &file.element(shape).unwrap().bound_elements;
+            assert_eq!(bound.len(), 1);
+            assert_eq!(bound[0].id, id);
+        }
+
+        let json = serde_json::to_value(arrow).unwrap();
+        assert_eq!(json["startBinding"]["elementId"], "a");
+        assert_eq!(json["endBinding"]["gap"], GAP);
+    }
+
+    #[test]
+    fn elbows() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(300., 100., 100., 50.).id("b").build(),
+                Rectangle::new(300., 300., 100., 50.).id("c").build(),
+                Rectangle::new(0., 100., 100., 50.).id("d").build(),
+            ],
+            ..Default::default()
+        };
+        let across = elbow_arrow(&mut file, "a", "b").unwrap();
+        let down = elbow_arrow(&mut file, "b", "c").unwrap();
+        let back = elbow_arrow(&mut file, "b", "d").unwrap();
+        let points = |id: &str| {
+            let arrow = file.element(id).unwrap();
+            let ElementKind::Arrow { points, .. } = &arrow.kind else {
+                panic!("not an arrow");
+            };
+            let absolute: Vec<_> = points
+                .iter()
+                .map(|p| [arrow.x + p[0], arrow.y + p[1]])
+                .collect();
+            absolute
+        };
+
+        let middle = 100. + GAP + (200. - 2. * GAP) / 2.;
+        assert_eq!(
+            points(&across),
+            [
+                [100. + GAP, 25.],
+                [middle, 25.],
+                [middle, 125.],
+                [300. - GAP, 125.]
+            ]
+        );
+        assert_eq!(file.element(&across).unwrap().height, 100.);
+        assert_eq!(file.element(&across).unwrap().roundness, None);
+        assert_eq!(points(&down), [[350., 150. + GAP], [350., 300. - GAP]]);
+        assert_eq!(points(&back), [[300. - GAP, 125.], [100. + GAP, 125.]]);
+
+        let ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } = &file.element(&down).unwrap().kind
+        else {
+            panic!("not an arrow");
+        };
+        assert_eq!(start_binding.as_ref().unwrap().element_id, "b");
+        assert_eq!(end_binding.as_ref().unwrap().element_id, "c");
+        assert_eq!(file.element("b").unwrap().bound_elements.len(), 3);
+        assert_eq!(file.validate(), Ok(()));
+    }
+
+    #[test]
+    fn missing_elements() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Rectangle::new(0., 0., 100., 50.).id("a").build()],
+            ..Default::default()
+        };
+        assert_eq!(bind_arrow(&mut file, "a", "b"), None);
+        assert_eq!(elbow_arrow(&mut file, "b", "a"), None);
+        assert_eq!(file.elements.len(), 1);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::{
+    id::random_id, Color, Element, ElementKind, FillStyle, FontFamily, Roundness, StrokeStyle,
+    TextAlign, TextElement, VerticalAlign,
+};
+
+// Setters for the properties every element has. Each builder is a wrapper around the `Element`
+// it's building.
+macro_rules! common_setters {
+    ($builder:ident) => {
+        impl $builder {
+            /// Ids must be unique within a file. Without one the builder makes up a random id,
+            /// see `IdGenerator`.
+            pub fn id(mut self, id: impl Into<String>) -> Self {
+                self.0.id = id.into();
+                self
+            }
+
+            /// Rotates the element clockwise around its center.
+            pub fn rotate(mut self, radians: f64) -> Self {
+                self.0.angle = radians;
+                self
+            }
+
+            pub fn stroke_color(mut self, color: Color) -> Self {
+                self.0.stroke_color = color.into();
+                self
+            }
+
+            pub fn background(mut self, color: Color) -> Self {
+                self.0.background_color = color.into();
+                self
+            }
+
+            pub fn stroke_width(mut self, width: f64) -> Self {
+                self.0.stroke_width = width;
+                self
+            }
+
+            pub fn stroke_style(mut self, style: StrokeStyle) -> Self {
+                self.0.stroke_style = style;
+                self
+            }
+
+            /// 0 is architect, clean lines. 1 is artist, what new shapes get, and 2 is
+            /// cartoonist.
+            pub fn roughness(mut self, roughness: u8) -> Self {
+                self.0.roughness = roughness;
+                self
+            }
+
+            /// 0 is invisible and 100 opaque, `ExcalidrawFile::validate` reports anything else.
+            pub fn opacity(mut self, opacity: f64) -> Self {
+                self.0.opacity = opacity;
+                self
+            }
+
+            pub fn seed(mut self, seed: i32) -> Self {
+                self.0.seed = seed;
+                self
+            }
+
+            /// Locked elements can't be selected or moved in Excalidraw, for backgrounds and
+            /// the like.
+            pub fn locked(mut self, locked: bool) -> Self {
+                self.0.locked = locked;
+                self
+            }
+
+            /// Makes the element link to `url`. Excalidraw shows a link icon on it that opens
+            /// the link.
+            pub fn link(mut self, url: impl Into<String>) -> Self {
+                self.0.link = Some(url.into());
+                self
+            }
+
+            pub fn build(self) -> Element {
+                self.0
+            }
+        }
+    };
+}
+
+// Setters for the shapes with a background.
+macro_rules! fill_setters {
+    ($builder:ident) => {
+        impl $builder {
+            pub fn fill_style(mut self, fill_style: FillStyle) -> Self {
+                self.0.fill_style = fill_style;
+                self
+            }
+        }
+    };
+}
+
+// Setters for the shapes with corners, which start out round like they do in Excalidraw.
+macro_rules! round_setters {
+    ($builder:ident, $roundness:expr) => {
+        impl $builder {
+            /// `false` for sharp corners.
+            pub fn round(mut self, round: bool) -> Self {
+                self.0.roundness = round.then_some($roundness);
+                self
+            }
+        }
+    };
+}
+
+pub struct Rectangle(Element);
+common_setters!(Rectangle);
+fill_setters!(Rectangle);
+round_setters!(Rectangle, Roundness::ADAPTIVE_RADIUS);
+
+impl Rectangle {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self(Element::new(
+            random_id(),
+            ElementKind::Rectangle,
+            x,
+            y,
+            width,
+            height,
+        ))
+        .round(true)
+    }
+}
+
+pub struct Ellipse(Element);
+common_setters!(Ellipse);
+fill_setters!(Ellipse);
+
+impl Ellipse {
+    /// The ellipse that fits in the box at (x, y).
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self(Element::new(
+            random_id(),
+            ElementKind::Ellipse,
+            x,
+            y,
+            width,
+            height,
+        ))
+    }
+}
+
+pub struct Diamond(Element);
+common_setters!(Diamond);
+fill_setters!(Diamond);
+round_setters!(Diamond, Roundness::PROPORTIONAL_RADIUS);
+
+impl Diamond {
+    /// The diamond with its corners on the middle of each side of the box at (x, y).
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self(Element::new(
+            random_id(),
+            ElementKind::Diamond,
+            x,
+            y,
+            width,
+            height,
+        ))
+        .round(true)
+    }
+}
+
+pub struct Text(Element);
+common_setters!(Text);
+
+impl Text {
+    /// Text with its top left corner at (x, y). The size is worked out from the text and font.
+    pub fn new(x: f64, y: f64, text: impl Into<String>) -> Self {
+        let kind = ElementKind::Text(TextElement::new(text));
+        let mut builder = Self(Element::new(random_id(), kind, x, y, 0., 0.));
+        builder.measure();
+        builder
+    }
+
+    pub fn font_size(mut self, size: f64) -> Self {
+        self.text().font_size = size;
+        self.measure();
+        self
+    }
+
+    pub fn font_family(mut self, family: FontFamily) -> Self {
+        self.text().font_family = family;
+        self.measure();
+        self
+    }
+
+    pub fn text_align(mut self, align: TextAlign) -> Self {
+        self.text().text_align = align;
+        self
+    }
+
+    pub fn vertical_align(mut self, align: VerticalAlign) -> Self {
+        self.text().vertical_align = align;
+        self
+    }
+
+    fn text(&mut self) -> &mut TextElement {
+        match &mut self.0.kind {
+            ElementKind::Text(text) => text,
+            _ => unreachable!("text builder without a text element"),
+        }
+    }
+
+    fn measure(&mut self) {
+        (self.0.width, self.0.height) = self.text().measure();
+    }
+}
+
+// Lines and arrows are positioned at their first point and the rest are relative to it.
+fn linear(kind: fn(Vec<[f64; 2]>) -> ElementKind, points: &[[f64; 2]]) -> Element {
+    let [x, y] = points[0];
+    let relative: Vec<_> = points.iter().map(|p| [p[0] - x, p[1] - y]).collect();
+    let extent = |axis: usize| {
+        let (min, max) = relative.iter().fold((0f64, 0f64), |(min, max), p| {
+            (min.min(p[axis]), max.max(p[axis]))
+        });
+        max - min
+    };
+    let (width, height) = (extent(0), extent(1));
+    Element::new(random_id(), kind(relative), x, y, width, height)
+}
+
+pub struct Line(Element);
+common_setters!(Line);
+round_setters!(Line, Roundness::PROPORTIONAL_RADIUS);
+
+impl Line {
+    /// A line through absolute `points`. Panics if there are none.
+    pub fn new(points: &[[f64; 2]]) -> Self {
+        Self(linear(|points| ElementKind::Line { points }, points)).round(true)
+    }
+}
+
+pub struct Arrow(Element);
+common_setters!(Arrow);
+round_setters!(Arrow, Roundness::PROPORTIONAL_RADIUS);
+
+impl Arrow {
+    /// An arrow from `start` to `end`.
+    pub fn new(start: [f64; 2], end: [f64; 2]) -> Self {
+        Self::through(&[start, end])
+    }
+
+    /// An arrow through absolute `points`, pointing at the last one. Panics if there are none.
+    pub fn through(points: &[[f64; 2]]) -> Self {
+        let arrow = |points| ElementKind::Arrow {
+            points,
+            start_binding: None,
+            end_binding: None,
+        };
+        Self(linear(arrow, points)).round(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectangle() {
+        let rectangle = Rectangle::new(1., 2., 3., 4.)
+            .id("r")
+            .stroke_color(Color::BLACK)
+            .background(Color::LIGHT_BLUE)
+            .fill_style(FillStyle::Hachure)
+            .stroke_style(StrokeStyle::Dashed)
+            .link("src/builder.rs")
+            .locked(true)
+            .build();
+        let expected = Element {
+            background_color: "#a5d8ff".into(),
+            fill_style: FillStyle::Hachure,
+            stroke_style: StrokeStyle::Dashed,
+            link: Some("src/builder.rs".into()),
+            locked: true,
+            roundness: Some(Roundness::ADAPTIVE_RADIUS),
+            seed: rectangle.seed,
+            ..Element::new("r", ElementKind::Rectangle, 1., 2., 3., 4.)
+        };
+        assert_eq!(rectangle, expected);
+    }
+
+    #[test]
+    fn corners_start_round() {
+        let diamond = Diamond::new(0., 0., 10., 10.).roughness(0).build();
+        assert_eq!(diamond.roundness, Some(Roundness::PROPORTIONAL_RADIUS));
+        assert_eq!(diamond.roughness, 0);
+        let json = serde_json::to_value(&diamond).unwrap();
+        assert_eq!(json["roundness"], serde_json::json!({"type": 2}));
+
+        let sharp = Rectangle::new(0., 0., 10., 10.).round(false).build();
+        assert!(serde_json::to_value(&sharp).unwrap()["roundness"].is_null());
+        assert_eq!(Ellipse::new(0., 0., 10., 10.).build().roundness, None);
+        assert_eq!(Text::new(0., 0., "hi").build().roundness, None);
+    }
+
+    #[test]
+    fn fractional_opacity_round_trips() {
+        let faint = Rectangle::new(0., 0., 10., 10.).opacity(37.5).build();
+        let json = serde_json::to_value(&faint).unwrap();
+        assert_eq!(json["opacity"], 37.5);
+        let back: Element = serde_json::from_value(json).unwrap();
+        assert_eq!(back.opacity, 37.5);
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let a = Ellipse::new(0., 0., 1., 1.).build();
+        let b = Ellipse::new(0., 0., 1., 1.).build();
+        assert_ne!(a.id, b.id);
+    }
+
+    #[test]
+    fn text_size_follows_the_font() {
+        let small = Text::new(0., 0., "hello").font_size(10.).build();
+        let large = Text::new(0., 0., "hello").font_size(40.).build();
+        assert!(small.width < large.width);
+        assert!(small.height < large.height);
+    }
+
+    #[test]
+    fn arrow_points_are_relative() {
+        let arrow = Arrow::through(&[[10., 10.], [40., -10.], [0., 30.]]).build();
+        assert_eq!((arrow.x, arrow.y), (10., 10.));
+        assert_eq!((arrow.width, arrow.height), (40., 40.));
+        assert_eq!(
+            arrow.kind,
+            ElementKind::Arrow {
+                points: vec![[0., 0.], [30., -20.], [-10., 20.]],
+                start_binding: None,
+                end_binding: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("`{0}` isn't a hex color.")]
+pub struct InvalidColor(pub String);
+
+// `#rgb`, `#rrggbb` or `#rrggbbaa`, or `transparent`. A `const fn` so `Color::from_static` can
+// check colors at compile time.
+pub(crate) const fn is_color(color: &str) -> bool {
+    let bytes = color.as_bytes();
+    if bytes.len() == 11 {
+        let transparent = b"transparent";
+        let mut i = 0;
+        while i < 11 && bytes[i] == transparent[i] {
+            i += 1;
+        }
+        return i == 11;
+    }
+    if !matches!(bytes.len(), 4 | 7 | 9) || bytes[0] != b'#' {
+        return false;
+    }
+    let mut i = 1;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_hexdigit() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// A color that's been checked to be `#rgb`, `#rrggbb`, `#rrggbbaa` or `transparent`. The
+/// builders only take checked colors, so a typo can't make a file Excalidraw draws wrong. Make
+/// one with `str::parse` or `TryFrom`, or `from_static` for constants.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Color(Cow<'static, str>);
+
+// The first row of Excalidraw's color picker, strokes and then the lighter backgrounds.
+impl Color {
+    pub const BLACK: Color = Color(Cow::Borrowed("#1e1e1e"));
+    pub const RED: Color = Color(Cow::Borrowed("#e03131"));
+    pub const GREEN: Color = Color(Cow::Borrowed("#2f9e44"));
+    pub const BLUE: Color = Color(Cow::Borrowed("#1971c2"));
+    pub const ORANGE: Color = Color(Cow::Borrowed("#f08c00"));
+
+    pub const TRANSPARENT: Color = Color(Cow::Borrowed("transparent"));
+    pub const LIGHT_RED: Color = Color(Cow::Borrowed("#ffc9c9"));
+    pub const LIGHT_GREEN: Color = Color(Cow::Borrowed("#b2f2bb"));
+    pub const LIGHT_BLUE: Color = Color(Cow::Borrowed("#a5d8ff"));
+    pub const LIGHT_YELLOW: Color = Color(Cow::Borrowed("#ffec99"));
+
+    /// For colors known up front. Panics if `color` isn't a color, which in a `const` is a
+    /// compile error:
+    ///
+    /// ```
+    /// const GRAY: exdraw::Color = exdraw::Color::from_static("#868e96");
+    /// ```
+    pub const fn from_static(color: &'static str) -> Color {
+        assert!(is_color(color), "not a hex color");
+        Color(Cow::Borrowed(color))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Color {
+    type Err = InvalidColor;
+
+    fn from_str(color: &str) -> Result<Self, Self::Err> {
+        if is_color(color) {
+            Ok(Color(Cow::Owned(color.to_string())))
+        } else {
+            Err(InvalidColor(color.to_string()))
+        }
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = InvalidColor;
+
+    fn try_from(color: &str) -> Result<Self, Self::Error> {
+        color.parse()
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = InvalidColor;
+
+    fn try_from(color: String) -> Result<Self, Self::Error> {
+        if is_color(&color) {
+            Ok(Color(Cow::Owned(color)))
+        } else {
+            Err(InvalidColor(color))
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.0.into_owned()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for color in ["#abc", "#A5D8FF", "#1e1e1e80", "transparent"] {
+            assert_eq!(color.parse::<Color>().unwrap().as_str(), color);
+        }
+        for color in ["#ff", "1e1e1e", "#12345", "#ggg", "red", ""] {
+            assert_eq!(color.parse::<Color>(), Err(InvalidColor(color.to_string())));
+        }
+    }
+
+    #[test]
+    fn palette_is_valid() {
+        for color in [
+            Color::BLACK,
+            Color::RED,
+            Color::GREEN,
+            Color::BLUE,
+            Color::ORANGE,
+            Color::TRANSPARENT,
+            Color::LIGHT_RED,
+            Color::LIGHT_GREEN,
+            Color::LIGHT_BLUE,
+            Color::LIGHT_YELLOW,
+        ] {
+            assert!(is_color(color.as_str()), "{color}");
+        }
+    }
+
+    #[test]
+    fn conversions_check_colors() {
+        assert_eq!(Color::try_from("#ff"), Err(InvalidColor("#ff".into())));
+        assert_eq!(
+            Color::try_from("#FFF".to_string()).map(String::from),
+            Ok("#FFF".to_string())
+        );
+        assert_eq!(Color::from_static("#e9ecef").as_str(), "#e9ecef");
+    }
+
+    #[test]
+    #[should_panic(expected = "not a hex color")]
+    fn bad_static_colors_panic() {
+        let color = "#ggg";
+        // Not a `const`, so it's checked when it runs instead of when it compiles.
+        Color::from_static(std::hint::black_box(color));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::{Element, ExcalidrawFile};
+
+/// What changed between two files, see `diff`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SceneDiff {
+    /// Ids of the elements only in the new file, in its order.
+    pub added: Vec<String>,
+    /// Ids of the elements only in the old file, in its order.
+    pub removed: Vec<String>,
+    /// Elements in both files that are different, in the new file's order.
+    pub modified: Vec<ElementChange>,
+}
+
+impl SceneDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// An element that's in both files but different.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementChange {
+    pub id: String,
+    /// The fields that changed, named like they are in the file, e.g. `strokeColor`.
+    pub fields: Vec<String>,
+}
+
+// The element's fields as they're saved.
+fn fields(element: &Element) -> Map<String, Value> {
+    match serde_json::to_value(element) {
+        Ok(Value::Object(fields)) => fields,
+        _ => unreachable!("elements serialize to objects"),
+    }
+}
+
+/// The elements added, removed and changed going from `a` to `b`, matched up by id. Moving an
+/// element up or down in `elements` isn't a change, and neither is its fractional `index`, which
+/// only says where it's drawn in that order. Only the elements are compared, not the editor
+/// settings or `files`.
+pub fn diff(a: &ExcalidrawFile, b: &ExcalidrawFile) -> SceneDiff {
+    let mut diff = SceneDiff::default();
+    for element in &b.elements {
+        let Some(old) = a.element(&element.id) else {
+            diff.added.push(element.id.clone());
+            continue;
+        };
+        let (old, new) = (fields(old), fields(element));
+        let changed: Vec<_> = old
+            .keys()
+            .chain(new.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|&field| field != "index" && old.get(field) != new.get(field))
+            .cloned()
+            .collect();
+        if !changed.is_empty() {
+            diff.modified.push(ElementChange {
+                id: element.id.clone(),
+                fields: changed,
+            });
+        }
+    }
+    diff.removed = a
+        .elements
+        .iter()
+        .filter(|element| b.element(&element.id).is_none())
+        .map(|element| element.id.clone())
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assign_indices, bring_to_front, seed_ids, Ellipse, Rectangle, Text};
+
+    // Seeded so two scenes are the same down to their elements' seeds.
+    fn scene() -> ExcalidrawFile {
+        seed_ids(0);
+        ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Ellipse::new(200., 0., 50., 50.).id("b").build(),
+                Text::new(0., 100., "hi").id("c").build(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_each_kind_of_change() {
+        let old = scene();
+        let mut new = scene();
+        new.elements.retain(|element| element.id != "b");
+        new.elements
+            .push(Rectangle::new(0., 0., 10., 10.).id("d").build());
+        let a = new.element_mut("a").unwrap();
+        a.x = 10.;
+        a.stroke_color = "#e03131".into();
+        let seed = old.element("c").unwrap().seed;
+        *new.element_mut("c").unwrap() = Text::new(0., 100., "bye").id("c").seed(seed).build();
+
+        let diff = diff(&old, &new);
+        assert_eq!(diff.added, ["d"]);
+        assert_eq!(diff.removed, ["b"]);
+        assert_eq!(
+            diff.modified,
+            [
+                ElementChange {
+                    id: "a".into(),
+                    fields: vec!["strokeColor".into(), "x".into()],
+                },
+                ElementChange {
+                    id: "c".into(),
+                    fields: vec!["text".into(), "width".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn order_isnt_a_change() {
+        let old = scene();
+        let mut new = scene();
+        assign_indices(&mut new);
+        bring_to_front(&mut new, "a").unwrap();
+        assert!(diff(&old, &new).is_empty());
+        assert!(diff(&new, &new).is_empty());
+    }
+}
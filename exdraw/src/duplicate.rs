@@ -0,0 +1,137 @@
+use crate::{
+    id::{random_id, random_seed},
+    order::assign_indices,
+    BoundElement, BoundElementKind, ElementKind, ExcalidrawFile,
+};
+
+/// Copies the element with this id, moved by `dx` and `dy`, and returns the copy's id. A shape's
+/// label is copied along with it, but arrows bound to the shape aren't, and a copied arrow isn't
+/// bound to anything. Copying a label on its own leaves it loose, outside the shape. The copy goes
+/// right after the element in `elements`, so it's drawn over it and stays in the same frame and
+/// groups. Every element's `index` is renumbered to match. Returns `None` if the id isn't in the
+/// file.
+pub fn duplicate(file: &mut ExcalidrawFile, id: &str, dx: f64, dy: f64) -> Option<String> {
+    let index = file.elements.iter().position(|element| element.id == id)?;
+    let mut copy = file.elements[index].clone();
+    copy.id = random_id();
+    copy.seed = random_seed();
+    copy.translate(dx, dy);
+    match &mut copy.kind {
+        ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } => {
+            *start_binding = None;
+            *end_binding = None;
+        }
+        // The container only lists the original.
+        ElementKind::Text(text) => text.container_id = None,
+        _ => {}
+    }
+
+    let mut labels = Vec::new();
+    for bound in &copy.bound_elements {
+        if bound.r#type != BoundElementKind::Text {
+            continue;
+        }
+        let Some(label) = file.element(&bound.id) else {
+            continue;
+        };
+        let mut label = label.clone();
+        label.id = random_id();
+        label.seed = random_seed();
+        label.translate(dx, dy);
+        if let ElementKind::Text(text) = &mut label.kind {
+            text.container_id = Some(copy.id.clone());
+        }
+        labels.push(label);
+    }
+    copy.bound_elements = labels
+        .iter()
+        .map(|label| BoundElement {
+            id: label.id.clone(),
+            r#type: BoundElementKind::Text,
+        })
+        .collect();
+
+    let copy_id = copy.id.clone();
+    file.elements
+        .splice(index + 1..index + 1, [copy].into_iter().chain(labels));
+    assign_indices(file);
+    Some(copy_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bind_arrow, layout_flowchart, Node, Rectangle};
+
+    #[test]
+    fn copies_labels_but_not_arrows() {
+        let mut file = layout_flowchart(&[Node::new("a"), Node::new("b")], &[(0, 1)]);
+        assign_indices(&mut file);
+        let copy = duplicate(&mut file, "node-0", 0., 100.).unwrap();
+        assert_eq!(file.validate(), Ok(()));
+        assert_eq!(file.elements.len(), 7);
+        assert_eq!(file.elements[1].id, copy);
+        // The copy doesn't share the original's index, they're renumbered in the new order.
+        assert!(file.elements.windows(2).all(|w| w[0].index < w[1].index));
+
+        let original = file.element("node-0").unwrap();
+        let duplicate = file.element(&copy).unwrap();
+        assert_eq!(duplicate.y, original.y + 100.);
+        assert_eq!(duplicate.bound_elements.len(), 1);
+        let label = file.element(&duplicate.bound_elements[0].id).unwrap();
+        let ElementKind::Text(text) = &label.kind else {
+            panic!("not text");
+        };
+        assert_eq!(text.text, "a");
+        assert_eq!(text.container_id.as_ref(), Some(&copy));
+        let original_label = file.element(&original.bound_elements[0].id).unwrap();
+        assert_eq!(label.y, original_label.y + 100.);
+    }
+
+    #[test]
+    fn copied_arrows_are_unbound() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 10., 10.).id("a").build(),
+                Rectangle::new(100., 0., 10., 10.).id("b").build(),
+            ],
+            ..Default::default()
+        };
+        let arrow = bind_arrow(&mut file, "a", "b").unwrap();
+        let copy = duplicate(&mut file, &arrow, 0., 50.).unwrap();
+        assert_eq!(file.validate(), Ok(()));
+        let ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } = &file.element(&copy).unwrap().kind
+        else {
+            panic!("not an arrow");
+        };
+        assert_eq!((start_binding, end_binding), (&None, &None));
+        assert_eq!(file.element("a").unwrap().bound_elements.len(), 1);
+    }
+
+    #[test]
+    fn copied_labels_are_loose() {
+        let mut file = layout_flowchart(&[Node::new("a")], &[]);
+        let label = file.element("node-0").unwrap().bound_elements[0].id.clone();
+        let copy = duplicate(&mut file, &label, 0., 100.).unwrap();
+        assert_eq!(file.validate(), Ok(()));
+        let ElementKind::Text(text) = &file.element(&copy).unwrap().kind else {
+            panic!("not text");
+        };
+        assert_eq!(text.container_id, None);
+        assert_eq!(file.element("node-0").unwrap().bound_elements.len(), 1);
+    }
+
+    #[test]
+    fn missing_element() {
+        let mut file = ExcalidrawFile::default();
+        assert_eq!(duplicate(&mut file, "nope", 1., 1.), None);
+    }
+}
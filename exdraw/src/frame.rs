@@ -0,0 +1,93 @@
+use crate::{id::random_id, order::assign_indices, Element, ElementKind, ExcalidrawFile};
+
+// Space between a frame and its contents.
+const PADDING: f64 = 20.;
+
+/// Adds a frame called `name` around the elements with ids `children` and returns its id. The
+/// frame is sized to fit them with some padding. Returns `None` without changing anything if one
+/// of the ids isn't in the file or there aren't any.
+///
+/// Excalidraw expects a frame's children to come right before it in `elements`, so they're moved
+/// to the end of the list followed by the frame. It draws the frame's background behind them.
+pub fn add_frame(file: &mut ExcalidrawFile, name: &str, children: &[&str]) -> Option<String> {
+    let bounds = children
+        .iter()
+        .map(|id| file.element(id).map(Element::bounding_box))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .reduce(|a, b| a.union(&b))?
+        .expand(PADDING);
+
+    let id = random_id();
+    let (mut contents, rest) = file
+        .elements
+        .drain(..)
+        .partition::<Vec<_>, _>(|element| children.contains(&element.id.as_str()));
+    file.elements = rest;
+    for element in &mut contents {
+        element.frame_id = Some(id.clone());
+    }
+    file.elements.extend(contents);
+
+    let kind = ElementKind::Frame {
+        name: Some(name.to_string()),
+    };
+    file.elements.push(Element::new(
+        id.clone(),
+        kind,
+        bounds.min_x,
+        bounds.min_y,
+        bounds.width(),
+        bounds.height(),
+    ));
+    assign_indices(file);
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rectangle;
+
+    #[test]
+    fn encloses_its_children() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(500., 500., 10., 10.).id("outside").build(),
+                Rectangle::new(200., 100., 100., 50.).id("b").build(),
+            ],
+            ..Default::default()
+        };
+        let id = add_frame(&mut file, "Section", &["a", "b"]).unwrap();
+
+        let ids: Vec<_> = file.elements.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["outside", "a", "b", id.as_str()]);
+
+        let frame = file.element(&id).unwrap();
+        let bounds = frame.bounding_box();
+        for child in ["a", "b"] {
+            let child = file.element(child).unwrap();
+            assert_eq!(child.frame_id.as_ref(), Some(&id));
+            let child = child.bounding_box();
+            assert!(bounds.contains([child.min_x, child.min_y]));
+            assert!(bounds.contains([child.max_x, child.max_y]));
+        }
+        assert_eq!(file.element("outside").unwrap().frame_id, None);
+
+        let json = serde_json::to_value(frame).unwrap();
+        assert_eq!(json["type"], "frame");
+        assert_eq!(json["name"], "Section");
+    }
+
+    #[test]
+    fn missing_children() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Rectangle::new(0., 0., 100., 50.).id("a").build()],
+            ..Default::default()
+        };
+        assert_eq!(add_frame(&mut file, "Section", &["a", "b"]), None);
+        assert_eq!(add_frame(&mut file, "Section", &[]), None);
+        assert_eq!(file.elements.len(), 1);
+    }
+}
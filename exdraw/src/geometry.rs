@@ -0,0 +1,333 @@
+use crate::{Element, ElementKind, ExcalidrawFile, Zoom};
+
+// The window size `fit_to_content` plans for, the file doesn't know the real one.
+const VIEWPORT_WIDTH: f64 = 1280.;
+const VIEWPORT_HEIGHT: f64 = 720.;
+// Space to leave around the content.
+const VIEWPORT_PADDING: f64 = 40.;
+// Excalidraw doesn't zoom out further than this.
+const MIN_ZOOM: f64 = 0.1;
+
+/// An axis aligned box, with y growing downwards like in Excalidraw.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    pub fn center(&self) -> [f64; 2] {
+        [
+            (self.min_x + self.max_x) / 2.,
+            (self.min_y + self.max_y) / 2.,
+        ]
+    }
+
+    /// Points on the edge count as inside.
+    pub fn contains(&self, [x, y]: [f64; 2]) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+
+    /// Whether the boxes overlap, touching counts.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    /// Grows the box by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x - margin,
+            min_y: self.min_y - margin,
+            max_x: self.max_x + margin,
+            max_y: self.max_y + margin,
+        }
+    }
+
+    // The smallest box containing all the points, there has to be at least one.
+    fn around(points: impl IntoIterator<Item = [f64; 2]>) -> BoundingBox {
+        points
+            .into_iter()
+            .map(|[x, y]| BoundingBox {
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            })
+            .reduce(|a, b| a.union(&b))
+            .expect("no points")
+    }
+
+    /// The smallest box containing both.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+}
+
+impl Element {
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    /// Scales the element's position and size around the origin. Text gets a bigger font and
+    /// the points of lines and arrows are spread out, stroke widths stay the same. Only the box of
+    /// an `ElementKind::Other` is scaled, whatever else its type has is left alone.
+    pub fn scale(&mut self, factor: f64) {
+        self.x *= factor;
+        self.y *= factor;
+        self.width *= factor;
+        self.height *= factor;
+        match &mut self.kind {
+            ElementKind::Line { points } | ElementKind::Arrow { points, .. } => {
+                for point in points {
+                    point[0] *= factor;
+                    point[1] *= factor;
+                }
+            }
+            ElementKind::Text(text) => text.font_size *= factor,
+            ElementKind::Rectangle
+            | ElementKind::Ellipse
+            | ElementKind::Diamond
+            | ElementKind::Image { .. }
+            | ElementKind::Frame { .. }
+            | ElementKind::Other(_) => {}
+        }
+    }
+
+    /// The box the element covers. Lines and arrows cover their points, which can be on either
+    /// side of `x` and `y`. Rotated elements cover their corners or points after rotating them
+    /// around the center, so the box can be bigger than the element's `width` and `height`.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let outline: Vec<_> = match &self.kind {
+            ElementKind::Line { points } | ElementKind::Arrow { points, .. } => points
+                .iter()
+                .map(|[x, y]| [self.x + x, self.y + y])
+                .chain([[self.x, self.y]])
+                .collect(),
+            _ => vec![
+                [self.x, self.y],
+                [self.x + self.width, self.y],
+                [self.x, self.y + self.height],
+                [self.x + self.width, self.y + self.height],
+            ],
+        };
+        let unrotated = BoundingBox::around(outline.iter().copied());
+        if self.angle == 0. {
+            return unrotated;
+        }
+        let [cx, cy] = unrotated.center();
+        let (sin, cos) = self.angle.sin_cos();
+        BoundingBox::around(outline.iter().map(|[x, y]| {
+            let (dx, dy) = (x - cx, y - cy);
+            [cx + dx * cos - dy * sin, cy + dx * sin + dy * cos]
+        }))
+    }
+}
+
+/// Moves every element in the file.
+pub fn translate_all(file: &mut ExcalidrawFile, dx: f64, dy: f64) {
+    for element in &mut file.elements {
+        element.translate(dx, dy);
+    }
+}
+
+/// Scales the whole drawing around the origin, see `Element::scale`.
+pub fn scale_all(file: &mut ExcalidrawFile, factor: f64) {
+    for element in &mut file.elements {
+        element.scale(factor);
+    }
+}
+
+impl ExcalidrawFile {
+    /// The box around every element that isn't deleted, `None` if there aren't any.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.elements
+            .iter()
+            .filter(|element| !element.is_deleted)
+            .map(Element::bounding_box)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Scrolls and zooms the app state so the drawing opens centered in a typical window,
+    /// zooming out if it doesn't fit but never in past 100%. Does nothing to an empty scene.
+    pub fn fit_to_content(&mut self) {
+        let Some(bounds) = self.bounding_box() else {
+            return;
+        };
+        let fit_x = (VIEWPORT_WIDTH - 2. * VIEWPORT_PADDING) / bounds.width();
+        let fit_y = (VIEWPORT_HEIGHT - 2. * VIEWPORT_PADDING) / bounds.height();
+        let zoom = fit_x.min(fit_y).clamp(MIN_ZOOM, 1.);
+        let center_x = bounds.min_x + bounds.width() / 2.;
+        let center_y = bounds.min_y + bounds.height() / 2.;
+        let app_state = &mut self.app_state;
+        app_state.zoom = Zoom { value: zoom };
+        app_state.scroll_x = VIEWPORT_WIDTH / (2. * zoom) - center_x;
+        app_state.scroll_y = VIEWPORT_HEIGHT / (2. * zoom) - center_y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seed_ids, Arrow, Ellipse, Rectangle, Text};
+
+    // Seeded so two scenes are the same down to their elements' seeds.
+    fn scene() -> ExcalidrawFile {
+        seed_ids(0);
+        ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(10., 20., 30., 40.).id("r").build(),
+                Text::new(-5., 7.5, "hello").id("t").build(),
+                Arrow::through(&[[0., 0.], [50., -25.], [100., 0.]])
+                    .id("a")
+                    .build(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn translate_and_back() {
+        let mut file = scene();
+        translate_all(&mut file, 12.5, -40.);
+        assert_eq!((file.elements[0].x, file.elements[0].y), (22.5, -20.));
+        translate_all(&mut file, -12.5, 40.);
+        assert_eq!(file, scene());
+    }
+
+    #[test]
+    fn scale_and_back() {
+        let mut file = scene();
+        let before = file.bounding_box().unwrap();
+        scale_all(&mut file, 2.);
+        let after = file.bounding_box().unwrap();
+        assert_eq!(after.width(), 2. * before.width());
+        assert_eq!(
+            after.center(),
+            [2. * before.center()[0], 2. * before.center()[1]]
+        );
+        scale_all(&mut file, 0.5);
+        assert_eq!(file, scene());
+    }
+
+    #[test]
+    fn rotated_boxes() {
+        let square = Rectangle::new(0., 0., 100., 100.).build();
+        let rotated = Rectangle::new(0., 0., 100., 100.)
+            .rotate(std::f64::consts::FRAC_PI_4)
+            .build();
+        let (before, after) = (square.bounding_box(), rotated.bounding_box());
+        assert!(after.width() > before.width());
+        assert!((after.width() - 100. * 2f64.sqrt()).abs() < 1e-9);
+        assert_eq!(after.center(), before.center());
+
+        // A quarter turn of a wide box makes it tall.
+        let wide = Rectangle::new(0., 0., 200., 100.)
+            .rotate(std::f64::consts::FRAC_PI_2)
+            .build()
+            .bounding_box();
+        assert!((wide.width() - 100.).abs() < 1e-9);
+        assert!((wide.height() - 200.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn box_math() {
+        let a = BoundingBox {
+            min_x: 0.,
+            min_y: 0.,
+            max_x: 10.,
+            max_y: 10.,
+        };
+        let b = BoundingBox {
+            min_x: 10.,
+            min_y: 5.,
+            max_x: 20.,
+            max_y: 30.,
+        };
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&b.expand(-1.)));
+        assert!(a.contains([10., 0.]));
+        assert!(!a.contains([10.1, 0.]));
+        assert_eq!(a.union(&b).center(), [10., 15.]);
+    }
+
+    #[test]
+    fn empty_scene() {
+        assert_eq!(ExcalidrawFile::default().bounding_box(), None);
+    }
+
+    #[test]
+    fn union_of_elements() {
+        let mut deleted = Rectangle::new(-500., -500., 1., 1.).build();
+        deleted.is_deleted = true;
+        let file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(10., 20., 30., 40.).build(),
+                Ellipse::new(100., -10., 10., 10.).build(),
+                Arrow::new([50., 100.], [0., 120.]).build(),
+                deleted,
+            ],
+            ..Default::default()
+        };
+        let expected = BoundingBox {
+            min_x: 0.,
+            min_y: -10.,
+            max_x: 110.,
+            max_y: 120.,
+        };
+        assert_eq!(file.bounding_box(), Some(expected));
+        assert_eq!(expected.width(), 110.);
+        assert_eq!(expected.height(), 130.);
+    }
+
+    #[test]
+    fn fit_to_content_centers_the_scene() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Rectangle::new(1000., 2000., 100., 50.).build()],
+            ..Default::default()
+        };
+        file.fit_to_content();
+        let app_state = &file.app_state;
+        assert_eq!(app_state.zoom.value, 1.);
+        // The center of the rectangle ends up in the middle of the window.
+        assert_eq!(1050. + app_state.scroll_x, VIEWPORT_WIDTH / 2.);
+        assert_eq!(2025. + app_state.scroll_y, VIEWPORT_HEIGHT / 2.);
+    }
+
+    #[test]
+    fn fit_to_content_zooms_out_of_big_scenes() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Rectangle::new(0., 0., 4000., 100.).build()],
+            ..Default::default()
+        };
+        file.fit_to_content();
+        let zoom = file.app_state.zoom.value;
+        assert!(zoom < 1.);
+        assert!(4000. * zoom <= VIEWPORT_WIDTH);
+    }
+
+    #[test]
+    fn fit_to_content_ignores_empty_scenes() {
+        let mut file = ExcalidrawFile::default();
+        file.fit_to_content();
+        assert_eq!(file.app_state, Default::default());
+    }
+}
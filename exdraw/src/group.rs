@@ -0,0 +1,69 @@
+use crate::{id::random_id, ExcalidrawFile};
+
+/// Puts the elements with these ids in a new group and returns its id. Groups nest, so elements
+/// that were already grouped together stay grouped inside the new one. Returns `None` without
+/// changing anything if one of the ids isn't in the file.
+pub fn group(file: &mut ExcalidrawFile, ids: &[&str]) -> Option<String> {
+    if !ids.iter().all(|id| file.element(id).is_some()) {
+        return None;
+    }
+    // Group ids look just like element ids.
+    let group_id = random_id();
+    for element in &mut file.elements {
+        if ids.contains(&element.id.as_str()) {
+            element.group_ids.push(group_id.clone());
+        }
+    }
+    Some(group_id)
+}
+
+/// Breaks up a group. Its elements stay in any other groups they're in.
+pub fn ungroup(file: &mut ExcalidrawFile, group_id: &str) {
+    for element in &mut file.elements {
+        element.group_ids.retain(|id| id != group_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rectangle, Text};
+
+    fn scene() -> ExcalidrawFile {
+        ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("box").build(),
+                Text::new(10., 10., "label").id("label").build(),
+                Rectangle::new(200., 0., 100., 50.).id("other").build(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn group_and_ungroup() {
+        let mut file = scene();
+        let inner = group(&mut file, &["box", "label"]).unwrap();
+        let outer = group(&mut file, &["box", "label", "other"]).unwrap();
+        assert_ne!(inner, outer);
+        assert_eq!(
+            file.element("box").unwrap().group_ids,
+            [inner.as_str(), outer.as_str()]
+        );
+        assert_eq!(file.element("other").unwrap().group_ids, [outer.as_str()]);
+
+        let json = serde_json::to_value(file.element("label").unwrap()).unwrap();
+        assert_eq!(json["groupIds"][0], inner.as_str());
+
+        ungroup(&mut file, &inner);
+        assert_eq!(file.element("box").unwrap().group_ids, [outer.as_str()]);
+        assert_eq!(file.element("label").unwrap().group_ids, [outer.as_str()]);
+    }
+
+    #[test]
+    fn missing_ids() {
+        let mut file = scene();
+        assert_eq!(group(&mut file, &["box", "nope"]), None);
+        assert!(file.elements.iter().all(|e| e.group_ids.is_empty()));
+    }
+}
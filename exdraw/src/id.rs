@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+
+// Same length as the ids Excalidraw makes.
+const ID_LENGTH: usize = 20;
+
+/// Makes random alphanumeric ids like the ones Excalidraw gives elements.
+///
+/// Ids have to be unique within a file, arrows, groups and frames refer to elements by id and
+/// get attached to the wrong ones otherwise. Random ids make collisions vanishingly unlikely.
+/// Builders and the functions that add elements use one generator per thread unless they're given
+/// an id, it also draws each new element's `seed`. Use `seed_ids` to get the same scene every run,
+/// in tests for example, or `seeded` for a generator of your own.
+pub struct IdGenerator {
+    rng: StdRng,
+}
+
+impl IdGenerator {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn next_id(&mut self) -> String {
+        (&mut self.rng)
+            .sample_iter(Alphanumeric)
+            .take(ID_LENGTH)
+            .map(char::from)
+            .collect()
+    }
+
+    /// A seed for an element's hand drawn look. Excalidraw's are positive like these.
+    pub fn next_seed(&mut self) -> i32 {
+        self.rng.gen_range(1..i32::MAX)
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static IDS: RefCell<IdGenerator> = RefCell::new(IdGenerator::new());
+}
+
+/// Restarts this thread's generator from `seed`, so the ids and seeds it gives the elements made
+/// after are the same every run.
+pub fn seed_ids(seed: u64) {
+    IDS.with(|ids| *ids.borrow_mut() = IdGenerator::seeded(seed));
+}
+
+/// A fresh id from this thread's generator.
+pub(crate) fn random_id() -> String {
+    IDS.with(|ids| ids.borrow_mut().next_id())
+}
+
+/// A fresh element seed from this thread's generator.
+pub(crate) fn random_seed() -> i32 {
+    IDS.with(|ids| ids.borrow_mut().next_seed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_frame, duplicate, group, ExcalidrawFile, Rectangle};
+
+    #[test]
+    fn seeded_ids_repeat() {
+        let mut a = IdGenerator::seeded(7);
+        let mut b = IdGenerator::seeded(7);
+        for _ in 0..10 {
+            assert_eq!(a.next_id(), b.next_id());
+        }
+        assert_ne!(IdGenerator::seeded(8).next_id(), b.next_id());
+    }
+
+    #[test]
+    fn seeded_scenes_repeat() {
+        let scene = || {
+            seed_ids(3);
+            let mut file = ExcalidrawFile {
+                elements: vec![
+                    Rectangle::new(0., 0., 10., 10.).build(),
+                    Rectangle::new(50., 0., 10., 10.).build(),
+                ],
+                ..Default::default()
+            };
+            let ids: Vec<_> = file.elements.iter().map(|e| e.id.clone()).collect();
+            group(&mut file, &[&ids[0], &ids[1]]).unwrap();
+            add_frame(&mut file, "Frame", &[&ids[0]]).unwrap();
+            duplicate(&mut file, &ids[1], 0., 20.).unwrap();
+            file
+        };
+        let file = scene();
+        assert_eq!(file, scene());
+        let seeds: std::collections::HashSet<_> = file.elements.iter().map(|e| e.seed).collect();
+        assert_eq!(seeds.len(), file.elements.len());
+    }
+
+    #[test]
+    fn ids_look_like_excalidraw_ids() {
+        let mut ids = IdGenerator::new();
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..1000 {
+            let id = ids.next_id();
+            assert_eq!(id.len(), ID_LENGTH);
+            assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
+            assert!(seen.insert(id));
+        }
+    }
+}
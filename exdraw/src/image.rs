@@ -0,0 +1,175 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::{id::random_id, Element, ElementKind, ExcalidrawFile};
+
+// Size to draw an image at when its header can't be read.
+const DEFAULT_SIZE: f64 = 100.;
+
+pub(crate) fn saved() -> String {
+    "saved".into()
+}
+
+pub(crate) fn unflipped() -> [f64; 2] {
+    [1., 1.]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+        }
+    }
+
+    // Width and height in pixels, from the image's header.
+    fn dimensions(self, bytes: &[u8]) -> Option<(u32, u32)> {
+        let u16_at = |i: usize| Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?));
+        let u32_at = |i: usize| Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+        match self {
+            // The IHDR chunk always comes first, right after the 8 byte signature.
+            ImageFormat::Png => {
+                if bytes.get(12..16)? != b"IHDR" {
+                    return None;
+                }
+                Some((u32_at(16)?, u32_at(20)?))
+            }
+            // Walk the segments until a start of frame, which has the size.
+            ImageFormat::Jpeg => {
+                let mut i = 2;
+                loop {
+                    if *bytes.get(i)? != 0xFF {
+                        return None;
+                    }
+                    let marker = *bytes.get(i + 1)?;
+                    let is_frame =
+                        (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+                    if is_frame {
+                        let height = u16_at(i + 5)?;
+                        let width = u16_at(i + 7)?;
+                        return Some((width.into(), height.into()));
+                    }
+                    i += 2 + u16_at(i + 2)? as usize;
+                }
+            }
+        }
+    }
+}
+
+/// An entry in `ExcalidrawFile::files`, the data of an image element.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryFile {
+    pub id: String,
+    pub mime_type: String,
+    /// The data as a `data:` URL.
+    #[serde(rename = "dataURL")]
+    pub data_url: String,
+    /// Unix time in milliseconds.
+    pub created: u64,
+}
+
+/// Embeds an image in the file and adds an element showing it at its natural size at (0, 0).
+/// Returns the element's id. The data is stored once per distinct image, keyed by its SHA-1
+/// like Excalidraw does, so adding the same image twice only stores it once.
+pub fn add_image(file: &mut ExcalidrawFile, bytes: &[u8], format: ImageFormat) -> String {
+    let file_id = sha1_smol::Sha1::from(bytes).digest().to_string();
+    if !file.files.contains_key(&file_id) {
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis() as u64);
+        let binary = BinaryFile {
+            id: file_id.clone(),
+            mime_type: format.mime_type().to_string(),
+            data_url: format!(
+                "data:{};base64,{}",
+                format.mime_type(),
+                STANDARD.encode(bytes)
+            ),
+            created,
+        };
+        let value = serde_json::to_value(binary).expect("binary files serialize");
+        file.files.insert(file_id.clone(), value);
+    }
+
+    let (width, height) = match format.dimensions(bytes) {
+        Some((width, height)) => (width as f64, height as f64),
+        None => (DEFAULT_SIZE, DEFAULT_SIZE),
+    };
+    let kind = ElementKind::Image {
+        file_id,
+        status: saved(),
+        scale: unflipped(),
+    };
+    let element = Element::new(random_id(), kind, 0., 0., width, height);
+    let id = element.id.clone();
+    file.elements.push(element);
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Just the signature and IHDR chunk of a 3x2 PNG, which is all `add_image` looks at.
+    const PNG: &[u8] = &[
+        0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0,
+        0, 3, 0, 0, 0, 2, 8, 6, 0, 0, 0,
+    ];
+
+    // Start of image, an APP0 segment and a baseline start of frame for a 32x16 JPEG.
+    const JPEG: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xE0, 0, 4, 0, 0, 0xFF, 0xC0, 0, 11, 8, 0, 16, 0, 32, 1, 1, 0x11, 0,
+    ];
+
+    #[test]
+    fn png() {
+        let mut file = ExcalidrawFile::default();
+        let id = add_image(&mut file, PNG, ImageFormat::Png);
+
+        let element = file.element(&id).unwrap();
+        assert_eq!((element.width, element.height), (3., 2.));
+        let ElementKind::Image { file_id, .. } = &element.kind else {
+            panic!("not an image");
+        };
+        let binary: BinaryFile = serde_json::from_value(file.files[file_id].clone()).unwrap();
+        assert_eq!(&binary.id, file_id);
+        assert_eq!(binary.mime_type, "image/png");
+        let data = binary
+            .data_url
+            .strip_prefix("data:image/png;base64,")
+            .unwrap();
+        assert_eq!(STANDARD.decode(data).unwrap(), PNG);
+
+        let json = serde_json::to_value(element).unwrap();
+        assert_eq!(json["type"], "image");
+        assert_eq!(json["fileId"], file_id.as_str());
+    }
+
+    #[test]
+    fn jpeg() {
+        let mut file = ExcalidrawFile::default();
+        let id = add_image(&mut file, JPEG, ImageFormat::Jpeg);
+        let element = file.element(&id).unwrap();
+        assert_eq!((element.width, element.height), (32., 16.));
+    }
+
+    #[test]
+    fn same_image_is_stored_once() {
+        let mut file = ExcalidrawFile::default();
+        add_image(&mut file, PNG, ImageFormat::Png);
+        add_image(&mut file, PNG, ImageFormat::Png);
+        add_image(&mut file, b"not an image", ImageFormat::Png);
+        assert_eq!(file.elements.len(), 3);
+        assert_eq!(file.files.len(), 2);
+        assert_eq!(file.elements[2].width, DEFAULT_SIZE);
+    }
+}
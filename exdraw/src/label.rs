@@ -0,0 +1,102 @@
+use crate::{
+    BoundElement, BoundElementKind, ElementKind, ExcalidrawFile, Text, TextAlign, VerticalAlign,
+};
+
+// Space Excalidraw leaves between a label and the edge of its container.
+const PADDING: f64 = 5.;
+
+/// Puts text inside the shape with id `container_id` and returns the text's id. The text is
+/// wrapped at spaces to the width of the shape and centered in it. If it doesn't fit the shape
+/// grows taller around its center, the way Excalidraw grows a shape as its label is typed.
+/// Returns `None` if the container isn't a rectangle, ellipse or diamond in the file.
+pub fn add_label(file: &mut ExcalidrawFile, container_id: &str, text: &str) -> Option<String> {
+    let container = file.element_mut(container_id)?;
+    // How much of the width and height of the box is inside the shape, around its middle.
+    let inside = match container.kind {
+        ElementKind::Rectangle => 1.,
+        ElementKind::Ellipse => std::f64::consts::FRAC_1_SQRT_2,
+        ElementKind::Diamond => 0.5,
+        _ => return None,
+    };
+
+    let mut label = Text::new(0., 0., text)
+        .text_align(TextAlign::Center)
+        .vertical_align(VerticalAlign::Middle)
+        .build();
+    let ElementKind::Text(label_text) = &mut label.kind else {
+        unreachable!("the text builder builds text");
+    };
+    label_text.wrap(container.width * inside - 2. * PADDING);
+    label_text.container_id = Some(container.id.clone());
+    (label.width, label.height) = label_text.measure();
+
+    let height = (label.height + 2. * PADDING) / inside;
+    if container.height < height {
+        container.y -= (height - container.height) / 2.;
+        container.height = height;
+    }
+    let [x, y] = container.bounding_box().center();
+    label.x = x - label.width / 2.;
+    label.y = y - label.height / 2.;
+    label.angle = container.angle;
+    container.bound_elements.push(BoundElement {
+        id: label.id.clone(),
+        r#type: BoundElementKind::Text,
+    });
+
+    let id = label.id.clone();
+    file.elements.push(label);
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arrow, Ellipse, Rectangle};
+
+    #[test]
+    fn centered_and_wrapped() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Rectangle::new(0., 0., 120., 100.).id("box").build()],
+            ..Default::default()
+        };
+        let id = add_label(&mut file, "box", "the quick brown fox").unwrap();
+        assert_eq!(file.validate(), Ok(()));
+
+        let label = file.element(&id).unwrap();
+        let ElementKind::Text(text) = &label.kind else {
+            panic!("not text");
+        };
+        assert_eq!(text.text, "the quick\nbrown fox");
+        assert_eq!(text.container_id.as_deref(), Some("box"));
+        assert_eq!(label.bounding_box().center(), [60., 50.]);
+        let container = file.element("box").unwrap();
+        assert_eq!(container.bound_elements[0].id, id);
+        assert_eq!(container.height, 100.);
+    }
+
+    #[test]
+    fn grows_to_fit() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Ellipse::new(0., 0., 200., 20.).id("e").build()],
+            ..Default::default()
+        };
+        let id = add_label(&mut file, "e", "one\ntwo").unwrap();
+        let container = file.element("e").unwrap();
+        let height = (50. + 2. * PADDING) * std::f64::consts::SQRT_2;
+        assert!((container.height - height).abs() < 1e-9);
+        assert_eq!(container.bounding_box().center()[1], 10.);
+        assert_eq!(file.element(&id).unwrap().bounding_box().center()[1], 10.);
+    }
+
+    #[test]
+    fn only_shapes_have_labels() {
+        let mut file = ExcalidrawFile {
+            elements: vec![Arrow::new([0., 0.], [10., 0.]).id("a").build()],
+            ..Default::default()
+        };
+        assert_eq!(add_label(&mut file, "a", "hi"), None);
+        assert_eq!(add_label(&mut file, "nope", "hi"), None);
+        assert_eq!(file.elements.len(), 1);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{add_label, bind_arrow, elbow_arrow, Element, ExcalidrawFile, Rectangle, Text};
+
+// Space between a node's label and the edge of its box.
+const NODE_PADDING: f64 = 20.;
+const MIN_NODE_WIDTH: f64 = 120.;
+// Space between the columns of a flowchart and between the nodes in a column.
+const RANK_GAP: f64 = 100.;
+const NODE_GAP: f64 = 40.;
+
+/// A labeled box in a generated diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub label: String,
+}
+
+impl Node {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+        }
+    }
+}
+
+// The size of a box big enough for the node's label.
+fn node_size(node: &Node) -> (f64, f64) {
+    let text = Text::new(0., 0., &node.label).build();
+    (
+        (text.width + 2. * NODE_PADDING).max(MIN_NODE_WIDTH),
+        text.height + 2. * NODE_PADDING,
+    )
+}
+
+// Each node's rank is the length of the longest path to it. Edges that would close a cycle are
+// left out, they're the ones that point backwards in the order a depth first search finishes.
+fn ranks(count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    fn visit(node: usize, edges: &[(usize, usize)], seen: &mut [bool], order: &mut Vec<usize>) {
+        seen[node] = true;
+        for &(from, to) in edges {
+            if from == node && !seen[to] {
+                visit(to, edges, seen, order);
+            }
+        }
+        order.push(node);
+    }
+
+    let mut seen = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+    for node in 0..count {
+        if !seen[node] {
+            visit(node, edges, &mut seen, &mut order);
+        }
+    }
+    order.reverse();
+    let mut index = vec![0; count];
+    for (i, &node) in order.iter().enumerate() {
+        index[node] = i;
+    }
+
+    let mut ranks = vec![0; count];
+    for &node in &order {
+        for &(from, to) in edges {
+            if from == node && index[to] > index[node] {
+                ranks[to] = ranks[to].max(ranks[node] + 1);
+            }
+        }
+    }
+    ranks
+}
+
+/// Draws a graph as a flowchart, left to right. Each node is a labeled rectangle with the id
+/// `node-{index}`, and each edge is an arrow bound to the two nodes. Nodes are put in columns by
+/// the longest path to them, and each column is ordered to follow the nodes before it so fewer
+/// arrows cross. Arrows are elbow arrows, see `elbow_arrow`, an edge that skips columns can
+/// cross the nodes in between. Edges from a node to itself aren't drawn.
+///
+/// Panics if an edge refers to a node that doesn't exist.
+pub fn layout_flowchart(nodes: &[Node], edges: &[(usize, usize)]) -> ExcalidrawFile {
+    for &(from, to) in edges {
+        assert!(
+            from < nodes.len() && to < nodes.len(),
+            "edge ({from}, {to}) refers to a missing node"
+        );
+    }
+    let ranks = ranks(nodes.len(), edges);
+    let mut columns = vec![Vec::new(); ranks.iter().max().map_or(0, |rank| rank + 1)];
+    for (node, &rank) in ranks.iter().enumerate() {
+        columns[rank].push(node);
+    }
+
+    // Sort each column by the average position of the nodes with edges into it, nodes with none
+    // keep their place.
+    let mut position = vec![0.; nodes.len()];
+    for column in &mut columns {
+        let barycenters: Vec<_> = column
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| {
+                let before: Vec<_> = edges
+                    .iter()
+                    .filter(|&&(from, to)| to == node && ranks[from] < ranks[node])
+                    .map(|&(from, _)| position[from])
+                    .collect();
+                if before.is_empty() {
+                    i as f64
+                } else {
+                    before.iter().sum::<f64>() / before.len() as f64
+                }
+            })
+            .collect();
+        let mut order: Vec<_> = (0..column.len()).collect();
+        order.sort_by(|&a, &b| barycenters[a].total_cmp(&barycenters[b]));
+        *column = order.into_iter().map(|i| column[i]).collect();
+        for (i, &node) in column.iter().enumerate() {
+            position[node] = i as f64;
+        }
+    }
+
+    let sizes: Vec<_> = nodes.iter().map(node_size).collect();
+    let column_height = |column: &[usize]| {
+        column.iter().map(|&node| sizes[node].1).sum::<f64>()
+            + NODE_GAP * column.len().saturating_sub(1) as f64
+    };
+    let tallest = columns
+        .iter()
+        .map(|column| column_height(column))
+        .fold(0., f64::max);
+
+    let mut boxes = vec![None; nodes.len()];
+    let mut x = 0.;
+    for column in &columns {
+        let column_width = column.iter().map(|&node| sizes[node].0).fold(0., f64::max);
+        let mut y = (tallest - column_height(column)) / 2.;
+        for &node in column {
+            let (width, height) = sizes[node];
+            boxes[node] = Some(
+                Rectangle::new(x + (column_width - width) / 2., y, width, height)
+                    .id(format!("node-{node}"))
+                    .build(),
+            );
+            y += height + NODE_GAP;
+        }
+        x += column_width + RANK_GAP;
+    }
+
+    draw(nodes, boxes, edges.iter().copied(), elbow_arrow)
+}
+
+/// How far apart `layout_tree` puts things.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeSpacing {
+    /// Space between neighbouring nodes on a level.
+    pub node_gap: f64,
+    /// Distance from the top of one level to the top of the next. Should be more than the height
+    /// of a node, which is 65 for a label on one line.
+    pub level_height: f64,
+}
+
+impl Default for TreeSpacing {
+    fn default() -> Self {
+        Self {
+            node_gap: 40.,
+            level_height: 140.,
+        }
+    }
+}
+
+/// Draws a tree top down, with each node centered over its children and an arrow from each
+/// parent to its children. `parents` has the index of each node's parent, `None` for the root.
+/// More than one root draws each tree next to the last. Nodes are labeled rectangles with the
+/// id `node-{index}`, like in `layout_flowchart`. Nodes that aren't below a root, because their
+/// parents make a cycle, are left out.
+///
+/// Panics if `parents` isn't the same length as `nodes` or refers to a node that doesn't exist.
+pub fn layout_tree(
+    nodes: &[Node],
+    parents: &[Option<usize>],
+    spacing: TreeSpacing,
+) -> ExcalidrawFile {
+    assert_eq!(nodes.len(), parents.len(), "every node needs a parent");
+    let mut children = vec![Vec::new(); nodes.len()];
+    let mut roots = Vec::new();
+    for (node, parent) in parents.iter().enumerate() {
+        match parent {
+            Some(parent) => children[*parent].push(node),
+            None => roots.push(node),
+        }
+    }
+    let sizes: Vec<_> = nodes.iter().map(node_size).collect();
+
+    // How wide each subtree is, the node or its children side by side, whichever is wider.
+    fn width(
+        node: usize,
+        children: &[Vec<usize>],
+        sizes: &[(f64, f64)],
+        gap: f64,
+        widths: &mut [f64],
+    ) -> f64 {
+        let below = children[node]
+            .iter()
+            .map(|&child| width(child, children, sizes, gap, widths) + gap)
+            .sum::<f64>()
+            - gap;
+        widths[node] = sizes[node].0.max(below);
+        widths[node]
+    }
+
+    let mut widths = vec![0.; nodes.len()];
+    let mut boxes = vec![None; nodes.len()];
+    let mut next_root = 0.;
+    let mut stack = Vec::new();
+    for &root in &roots {
+        width(root, &children, &sizes, spacing.node_gap, &mut widths);
+        stack.push((root, next_root, 0.));
+        next_root += widths[root] + spacing.node_gap;
+        // Each subtree fills the space from `left` to `left + widths[node]`, its children take up
+        // the middle of it.
+        while let Some((node, left, top)) = stack.pop() {
+            let (width, height) = sizes[node];
+            boxes[node] = Some(
+                Rectangle::new(left + (widths[node] - width) / 2., top, width, height)
+                    .id(format!("node-{node}"))
+                    .build(),
+            );
+            let below = children[node]
+                .iter()
+                .map(|&child| widths[child] + spacing.node_gap)
+                .sum::<f64>()
+                - spacing.node_gap;
+            let mut x = left + (widths[node] - below) / 2.;
+            for &child in &children[node] {
+                stack.push((child, x, top + spacing.level_height));
+                x += widths[child] + spacing.node_gap;
+            }
+        }
+    }
+
+    let edges = parents
+        .iter()
+        .enumerate()
+        .filter_map(|(node, parent)| Some(((*parent)?, node)));
+    draw(nodes, boxes, edges, bind_arrow)
+}
+
+// The labeled boxes for the nodes that have one and arrows for the edges between them, drawn by
+// `arrow`.
+fn draw(
+    nodes: &[Node],
+    boxes: Vec<Option<Element>>,
+    edges: impl Iterator<Item = (usize, usize)>,
+    arrow: fn(&mut ExcalidrawFile, &str, &str) -> Option<String>,
+) -> ExcalidrawFile {
+    let mut file = ExcalidrawFile::default();
+    for (node, rectangle) in nodes.iter().zip(boxes) {
+        if let Some(rectangle) = rectangle {
+            let id = rectangle.id.clone();
+            file.elements.push(rectangle);
+            add_label(&mut file, &id, &node.label);
+        }
+    }
+    for (from, to) in edges {
+        // Edges to nodes that weren't drawn are left out too, `arrow` skips them.
+        if from != to {
+            arrow(&mut file, &format!("node-{from}"), &format!("node-{to}"));
+        }
+    }
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ElementKind;
+
+    fn nodes(labels: &[&str]) -> Vec<Node> {
+        labels.iter().map(|&label| Node::new(label)).collect()
+    }
+
+    #[test]
+    fn ranks_follow_the_longest_path() {
+        assert_eq!(ranks(4, &[(0, 1), (1, 2), (0, 2), (3, 2)]), [0, 1, 2, 0]);
+        // The edge back to the start of the cycle is left out.
+        assert_eq!(ranks(3, &[(0, 1), (1, 2), (2, 0)]), [0, 1, 2]);
+    }
+
+    #[test]
+    fn flowchart() {
+        let file = layout_flowchart(
+            &nodes(&["start", "left", "right", "end"]),
+            &[(0, 1), (0, 2), (1, 3), (2, 3)],
+        );
+        assert_eq!(file.validate(), Ok(()));
+        // Four boxes, their labels and an arrow for each edge.
+        assert_eq!(file.elements.len(), 12);
+
+        let node = |i: usize| file.element(&format!("node-{i}")).unwrap();
+        assert!(node(0).x < node(1).x);
+        assert_eq!(node(1).x, node(2).x);
+        assert!(node(1).y < node(2).y);
+        assert!(node(2).x < node(3).x);
+        // Columns are centered on each other.
+        assert_eq!(node(0).bounding_box().center()[1], 85.);
+        assert_eq!(node(3).bounding_box().center()[1], 85.);
+
+        let label = file.element(&node(0).bound_elements[0].id).unwrap();
+        let ElementKind::Text(text) = &label.kind else {
+            panic!("not text");
+        };
+        assert_eq!(text.text, "start");
+        assert_eq!(text.container_id.as_deref(), Some("node-0"));
+        assert_eq!(
+            label.bounding_box().center(),
+            node(0).bounding_box().center()
+        );
+
+        // The arrows only turn at right angles.
+        for arrow in &file.elements {
+            if let ElementKind::Arrow { points, .. } = &arrow.kind {
+                assert!(points
+                    .windows(2)
+                    .all(|pair| pair[0][0] == pair[1][0] || pair[0][1] == pair[1][1]));
+            }
+        }
+    }
+
+    #[test]
+    fn cycles_and_loops() {
+        let file = layout_flowchart(&nodes(&["a", "b"]), &[(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(file.validate(), Ok(()));
+        let arrows = file
+            .elements
+            .iter()
+            .filter(|element| matches!(element.kind, ElementKind::Arrow { .. }));
+        assert_eq!(arrows.count(), 2);
+    }
+
+    #[test]
+    fn empty_graph() {
+        assert!(layout_flowchart(&[], &[]).elements.is_empty());
+        assert!(layout_tree(&[], &[], TreeSpacing::default())
+            .elements
+            .is_empty());
+    }
+
+    #[test]
+    fn tree() {
+        //       root
+        //      /    \
+        //     a      b
+        //           / \
+        //          c   d
+        let spacing = TreeSpacing {
+            node_gap: 10.,
+            level_height: 100.,
+        };
+        let file = layout_tree(
+            &nodes(&["root", "a", "b", "c", "d"]),
+            &[None, Some(0), Some(0), Some(2), Some(2)],
+            spacing,
+        );
+        assert_eq!(file.validate(), Ok(()));
+        assert_eq!(file.elements.len(), 14);
+
+        let node = |i: usize| file.element(&format!("node-{i}")).unwrap();
+        let center = |i: usize| node(i).bounding_box().center();
+        assert_eq!(node(0).y, 0.);
+        assert_eq!(node(1).y, 100.);
+        assert_eq!(node(3).y, 200.);
+        // Every node is as wide as the smallest box, so b's subtree is twice as wide as a's.
+        assert_eq!(node(1).x, 0.);
+        assert_eq!(node(3).x, MIN_NODE_WIDTH + 10.);
+        assert_eq!(node(4).x, 2. * (MIN_NODE_WIDTH + 10.));
+        assert_eq!(center(2)[0], (center(3)[0] + center(4)[0]) / 2.);
+        assert_eq!(center(0)[0], (0. + 3. * MIN_NODE_WIDTH + 2. * 10.) / 2.);
+    }
+
+    #[test]
+    fn forests_and_cycles() {
+        let file = layout_tree(
+            &nodes(&["a", "b", "c", "d"]),
+            &[None, None, Some(3), Some(2)],
+            TreeSpacing::default(),
+        );
+        assert_eq!(file.validate(), Ok(()));
+        assert!(file.element("node-0").unwrap().x < file.element("node-1").unwrap().x);
+        assert_eq!(file.element("node-2"), None);
+        assert_eq!(file.elements.len(), 4);
+    }
+}
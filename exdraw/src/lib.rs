@@ -1,11 +1,55 @@
-use serde::Serialize;
+use std::{io, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Map, Value};
 
+mod align;
+mod binding;
+mod builder;
+mod color;
+mod diff;
+mod duplicate;
+mod frame;
+mod geometry;
+mod group;
+mod id;
+mod image;
+mod label;
+mod layout;
+mod lock;
+mod merge;
+mod order;
+mod subset;
+mod svg;
+mod text;
+mod validate;
+
+pub use align::{align, distribute, flip_horizontal, flip_vertical, Alignment, Axis};
+pub use binding::{bind_arrow, elbow_arrow, Binding, BoundElement, BoundElementKind};
+pub use builder::{Arrow, Diamond, Ellipse, Line, Rectangle, Text};
+pub use color::{Color, InvalidColor};
+pub use diff::{diff, ElementChange, SceneDiff};
+pub use duplicate::duplicate;
+pub use frame::add_frame;
+pub use geometry::{scale_all, translate_all, BoundingBox};
+pub use group::{group, ungroup};
+pub use id::{seed_ids, IdGenerator};
+pub use image::{add_image, BinaryFile, ImageFormat};
+pub use label::add_label;
+pub use layout::{layout_flowchart, layout_tree, Node, TreeSpacing};
+pub use lock::lock_all;
+pub use order::{assign_indices, bring_to_front, reorder, send_to_back};
+pub use text::{FontFamily, TextAlign, TextElement, VerticalAlign};
+pub use validate::ValidationError;
+
 // This is copied almost exactly from
 // https://github.com/etolbakov/excalidocker-rs/blob/main/src/exporters/excalidraw.rs
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
+/// A whole `.excalidraw` file. Files can be loaded with `from_reader` or `str::parse` and missing
+/// fields get Excalidraw's defaults. Elements keep whatever this crate doesn't model, so they're
+/// saved the way they were loaded, but anything else it doesn't model is skipped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct ExcalidrawFile {
     pub r#type: String,
     pub version: i32,
@@ -15,6 +59,258 @@ pub struct ExcalidrawFile {
     pub files: Map<String, Value>,
 }
 
+/// A shape in the scene. The fields here are the ones every element type shares, `kind` holds
+/// the type and anything specific to it. It's flattened so the JSON has the `type` field next to
+/// the common properties, the way Excalidraw stores elements.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(
+    remote = "Self",
+    rename_all = "camelCase",
+    default = "Element::missing"
+)]
+pub struct Element {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: ElementKind,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Rotation around the center, in radians.
+    pub angle: f64,
+    pub stroke_color: String,
+    pub background_color: String,
+    /// How the background is filled in, only shapes have one.
+    pub fill_style: FillStyle,
+    pub stroke_width: f64,
+    pub stroke_style: StrokeStyle,
+    /// How sketchy the shape looks, 0 is architect, 1 artist and 2 cartoonist.
+    pub roughness: u8,
+    /// `None` for sharp corners.
+    pub roundness: Option<Roundness>,
+    /// 0 to 100, fully transparent to opaque.
+    pub opacity: f64,
+    /// Seeds the hand drawn look so the same element is drawn the same way every time.
+    /// New elements get a random one, see `seed_ids`.
+    pub seed: i32,
+    /// Excalidraw bumps these whenever it edits an element, to reconcile changes.
+    pub version: i32,
+    pub version_nonce: i32,
+    pub is_deleted: bool,
+    /// Locked elements can't be selected, moved or edited in Excalidraw until they're unlocked.
+    pub locked: bool,
+    /// The groups the element is in, innermost first. Elements sharing a group id are selected
+    /// and moved together.
+    pub group_ids: Vec<String>,
+    /// The frame the element is in.
+    pub frame_id: Option<String>,
+    /// Where clicking the element's link icon goes, a URL or a link to another element.
+    pub link: Option<String>,
+    /// Arrows bound to this element and the text inside it. They point back at it with their
+    /// bindings and `container_id`.
+    #[serde(deserialize_with = "null_as_default")]
+    pub bound_elements: Vec<BoundElement>,
+    /// Newer versions of Excalidraw order elements by this fractional index instead of their
+    /// place in `elements`, see `assign_indices`. Without one Excalidraw makes one up from the
+    /// element's place.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// Anything you want to keep with the element, like what it stands for. Excalidraw doesn't
+    /// look at it and keeps it through edits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_data: Option<Value>,
+    /// Everything else Excalidraw saves about the element, like its arrowheads or when it was
+    /// last updated, kept as is.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Serialize for Element {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Element::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut element = Element::deserialize(deserializer)?;
+        // `kind` and `other` are both flattened so both get the fields of the element's type.
+        // They're only kept in `kind`, or they'd be saved twice.
+        if let Ok(Value::Object(kind)) = serde_json::to_value(&element.kind) {
+            element.other.retain(|field, _| !kind.contains_key(field));
+        }
+        Ok(element)
+    }
+}
+
+impl Element {
+    // Fills in whatever a file leaves out. The type is always there.
+    fn missing() -> Self {
+        Self::new("", ElementKind::Rectangle, 0., 0., 0., 0.)
+    }
+
+    /// An element with the same defaults Excalidraw gives a new shape, a thin black outline and
+    /// no fill.
+    pub fn new(
+        id: impl Into<String>,
+        kind: ElementKind,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            kind,
+            x,
+            y,
+            width,
+            height,
+            angle: 0.,
+            stroke_color: Color::BLACK.into(),
+            background_color: Color::TRANSPARENT.into(),
+            fill_style: FillStyle::Solid,
+            stroke_width: 1.,
+            stroke_style: StrokeStyle::Solid,
+            roughness: 1,
+            roundness: None,
+            opacity: 100.,
+            seed: id::random_seed(),
+            version: 1,
+            version_nonce: 0,
+            is_deleted: false,
+            locked: false,
+            group_ids: vec![],
+            frame_id: None,
+            link: None,
+            bound_elements: vec![],
+            index: None,
+            custom_data: None,
+            other: Map::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(remote = "Self", tag = "type", rename_all = "lowercase")]
+pub enum ElementKind {
+    Rectangle,
+    Ellipse,
+    Diamond,
+    Text(TextElement),
+    /// A picture from the file's `files`, see `add_image`.
+    #[serde(rename_all = "camelCase")]
+    Image {
+        file_id: String,
+        /// `"saved"` once the image data is in the file.
+        #[serde(default = "image::saved")]
+        status: String,
+        /// `[-1, 1]` flips the image horizontally and `[1, -1]` vertically.
+        #[serde(default = "image::unflipped")]
+        scale: [f64; 2],
+    },
+    /// A titled section of the scene, see `add_frame`.
+    Frame {
+        name: Option<String>,
+    },
+    /// `points` are relative to the element's `x` and `y` and the first one is always `[0, 0]`.
+    Line {
+        points: Vec<[f64; 2]>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Arrow {
+        points: Vec<[f64; 2]>,
+        #[serde(default)]
+        start_binding: Option<Binding>,
+        #[serde(default)]
+        end_binding: Option<Binding>,
+    },
+    /// A type this crate doesn't model, like `freedraw` or `embeddable`. It's kept as it is in
+    /// the file, `type` included, without the fields every element has.
+    #[serde(skip)]
+    Other(Map<String, Value>),
+}
+
+impl Serialize for ElementKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ElementKind::Other(fields) => fields.serialize(serializer),
+            kind => ElementKind::serialize(kind, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ElementKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = Map::<String, Value>::deserialize(deserializer)?;
+        match fields.get("type") {
+            None => Err(de::Error::missing_field("type")),
+            Some(Value::String(kind))
+                if matches!(
+                    kind.as_str(),
+                    "rectangle"
+                        | "ellipse"
+                        | "diamond"
+                        | "text"
+                        | "image"
+                        | "frame"
+                        | "line"
+                        | "arrow"
+                ) =>
+            {
+                ElementKind::deserialize(Value::Object(fields)).map_err(de::Error::custom)
+            }
+            Some(_) => Ok(ElementKind::Other(fields)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StrokeStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+/// Excalidraw draws hachure and cross-hatch fills as sketchy diagonal lines in the background
+/// color. New shapes in the app get hachure but solid looks cleaner in generated drawings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FillStyle {
+    Hachure,
+    CrossHatch,
+    #[default]
+    Solid,
+    /// Zigzag lines, newer versions of Excalidraw have it.
+    Zigzag,
+}
+
+/// How Excalidraw rounds an element's corners.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Roundness {
+    /// 2 for `PROPORTIONAL_RADIUS` and 3 for `ADAPTIVE_RADIUS`. Old files have 1, which is drawn
+    /// like 2.
+    pub r#type: u8,
+    /// A fixed radius, Excalidraw doesn't set it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+}
+
+impl Roundness {
+    /// A radius that grows with the element, what new lines, arrows and diamonds get.
+    pub const PROPORTIONAL_RADIUS: Self = Self {
+        r#type: 2,
+        value: None,
+    };
+    /// The same radius on every size but small ones, what new rectangles get.
+    pub const ADAPTIVE_RADIUS: Self = Self {
+        r#type: 3,
+        value: None,
+    };
+}
+
 impl Default for ExcalidrawFile {
     fn default() -> Self {
         Self {
@@ -28,33 +324,397 @@ impl Default for ExcalidrawFile {
     }
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
+impl ExcalidrawFile {
+    /// An empty file that opens in dark mode, see `AppState::dark`.
+    pub fn dark() -> Self {
+        Self {
+            app_state: AppState::dark(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_reader(reader: impl io::Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Writes the file as it's serialized, without building the whole JSON in memory first.
+    /// Wrap files and sockets in a `BufWriter`, this makes lots of small writes.
+    pub fn to_writer<W: io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Like `to_writer` but indented, for files people will read or diff.
+    pub fn to_writer_pretty<W: io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    pub fn element(&self, id: &str) -> Option<&Element> {
+        self.elements.iter().find(|element| element.id == id)
+    }
+
+    pub fn element_mut(&mut self, id: &str) -> Option<&mut Element> {
+        self.elements.iter_mut().find(|element| element.id == id)
+    }
+}
+
+impl FromStr for ExcalidrawFile {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct AppState {
-    pub grid_size: i32,
+    /// `None` turns the grid off.
+    pub grid_size: Option<i32>,
     pub view_background_color: String,
+    /// Where the canvas is scrolled to. A point at `(x, y)` in the scene is drawn at
+    /// `((x + scroll_x) * zoom, (y + scroll_y) * zoom)` on screen.
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    pub zoom: Zoom,
+    pub theme: Theme,
+    /// Everything else Excalidraw saves about the editor, kept as is.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            grid_size: 20,
+            grid_size: Some(20),
             view_background_color: "#ffffff".into(),
+            scroll_x: 0.,
+            scroll_y: 0.,
+            zoom: Zoom { value: 1. },
+            theme: Theme::Light,
+            other: Map::new(),
         }
     }
 }
 
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
+impl AppState {
+    /// Opens in dark mode.
+    ///
+    /// Excalidraw doesn't store separate colors for dark mode, it draws the whole canvas through
+    /// an inverting filter instead. So the background stays white in the file and shows up
+    /// almost black, and the default dark gray strokes show up light, with nothing else to do.
+    pub fn dark() -> Self {
+        Self {
+            theme: Theme::Dark,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Zoom {
+    pub value: f64,
+}
+
+// Excalidraw writes `null` for some empty fields.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // Trimmed down from a file exported by excalidraw.com.
+    const EXPORTED: &str = r##"{
+        "type": "excalidraw",
+        "version": 2,
+        "source": "https://excalidraw.com",
+        "elements": [
+            {
+                "id": "box", "type": "rectangle", "x": 100, "y": 120.5, "width": 200,
+                "height": 80, "angle": 0, "strokeColor": "#1e1e1e",
+                "backgroundColor": "#a5d8ff", "fillStyle": "hachure", "strokeWidth": 2,
+                "strokeStyle": "solid", "roughness": 1, "opacity": 100, "groupIds": [],
+                "frameId": null, "roundness": { "type": 3 }, "seed": 1146824383,
+                "version": 12, "versionNonce": 1791459775, "isDeleted": false,
+                "boundElements": [{ "type": "arrow", "id": "arrow" }],
+                "updated": 1690000000000, "link": null, "locked": false
+            },
+            {
+                "id": "label", "type": "text", "x": 150, "y": 150, "width": 60, "height": 25,
+                "angle": 0, "strokeColor": "#1e1e1e", "backgroundColor": "transparent",
+                "fillStyle": "hachure", "strokeWidth": 1, "roughness": 1, "opacity": 100,
+                "seed": 1, "version": 3, "versionNonce": 2, "isDeleted": false,
+                "boundElements": null, "text": "Hello", "fontSize": 20, "fontFamily": 5,
+                "textAlign": "center", "verticalAlign": "middle", "baseline": 18,
+                "containerId": null, "originalText": "Hello", "lineHeight": 1.25
+            },
+            {
+                "id": "arrow", "type": "arrow", "x": 300, "y": 160, "width": 100, "height": 0,
+                "angle": 0, "strokeColor": "#1e1e1e", "backgroundColor": "transparent",
+                "fillStyle": "hachure", "strokeWidth": 2, "roughness": 1, "opacity": 100,
+                "seed": 5, "version": 8, "versionNonce": 9, "isDeleted": false,
+                "boundElements": null, "points": [[0, 0], [100, 0]],
+                "lastCommittedPoint": null,
+                "startBinding": { "elementId": "box", "focus": 0.1, "gap": 1 },
+                "endBinding": null, "startArrowhead": null, "endArrowhead": "arrow"
+            }
+        ],
+        "appState": {
+            "gridSize": null, "viewBackgroundColor": "#ffffff", "theme": "light",
+            "currentItemFontFamily": 1
+        },
+        "files": {}
+    }"##;
+
+    #[test]
+    fn deserialize_exported_file() {
+        let file: ExcalidrawFile = EXPORTED.parse().unwrap();
+        assert_eq!(file.source.as_deref(), Some("https://excalidraw.com"));
+        assert_eq!(file.elements.len(), 3);
+
+        let rectangle = &file.elements[0];
+        assert_eq!(rectangle.kind, ElementKind::Rectangle);
+        assert_eq!((rectangle.x, rectangle.y), (100., 120.5));
+        assert_eq!(rectangle.fill_style, FillStyle::Hachure);
+        assert_eq!(rectangle.stroke_style, StrokeStyle::Solid);
+        assert_eq!(rectangle.bound_elements[0].id, "arrow");
+        assert_eq!(rectangle.roundness, Some(Roundness::ADAPTIVE_RADIUS));
+        assert_eq!(rectangle.opacity, 100.);
+        assert_eq!(file.elements[1].roundness, None);
+
+        let ElementKind::Text(text) = &file.elements[1].kind else {
+            panic!("not text");
+        };
+        assert_eq!(text.text, "Hello");
+        assert_eq!(text.font_family, FontFamily::Other(5));
+        assert_eq!(text.text_align, TextAlign::Center);
+        assert!(file.elements[1].bound_elements.is_empty());
+
+        let ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } = &file.elements[2].kind
+        else {
+            panic!("not an arrow");
+        };
+        assert_eq!(start_binding.as_ref().unwrap().element_id, "box");
+        assert_eq!(*end_binding, None);
+
+        assert_eq!(file.app_state.grid_size, None);
+        assert_eq!(file.app_state.theme, Theme::Light);
+        assert_eq!(file.app_state.other["currentItemFontFamily"], 1);
+    }
+
+    #[test]
+    fn round_trip() {
+        let file: ExcalidrawFile = EXPORTED.parse().unwrap();
+        let json = serde_json::to_string(&file).unwrap();
+        let again = ExcalidrawFile::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(file, again);
+    }
+
+    #[test]
+    fn keeps_what_it_doesnt_model() {
+        let file: ExcalidrawFile = EXPORTED.parse().unwrap();
+        assert_eq!(file.elements[0].other["updated"], 1690000000000u64);
+        assert_eq!(file.elements[1].other["originalText"], "Hello");
+        let arrow = &file.elements[2];
+        assert_eq!(arrow.other["endArrowhead"], "arrow");
+        assert!(!arrow.other.contains_key("points"));
+        assert!(!arrow.other.contains_key("type"));
+
+        let exported: Value = serde_json::from_str(EXPORTED).unwrap();
+        let saved = serde_json::to_value(&file).unwrap();
+        for (exported, saved) in exported["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .zip(saved["elements"].as_array().unwrap())
+        {
+            for field in exported.as_object().unwrap().keys() {
+                assert!(saved.get(field).is_some(), "{} wasn't saved", field);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_types_round_trip() {
+        let json = r#"{"elements": [{
+            "type": "freedraw", "id": "f", "x": 10, "points": [[0, 0], [5, 5]],
+            "pressures": [], "simulatePressure": true
+        }]}"#;
+        let file: ExcalidrawFile = json.parse().unwrap();
+        let element = &file.elements[0];
+        assert_eq!(element.x, 10.);
+        assert!(element.other.is_empty());
+        let ElementKind::Other(fields) = &element.kind else {
+            panic!("freedraw is modeled");
+        };
+        assert_eq!(fields["type"], "freedraw");
+        assert_eq!(fields["points"][1][0], 5);
+        assert!(!fields.contains_key("x"));
+
+        let saved = serde_json::to_value(&file).unwrap();
+        assert_eq!(saved["elements"][0]["type"], "freedraw");
+        assert_eq!(saved["elements"][0]["simulatePressure"], true);
+        assert_eq!(saved.to_string().parse::<ExcalidrawFile>().unwrap(), file);
+
+        let untyped = r#"{"elements": [{"id": "e"}]}"#;
+        assert!(untyped.parse::<ExcalidrawFile>().is_err());
+    }
+
+    #[test]
+    fn writes_the_same_json() {
+        let file: ExcalidrawFile = EXPORTED.parse().unwrap();
+        let mut compact = vec![];
+        file.to_writer(&mut compact).unwrap();
+        assert_eq!(compact, serde_json::to_vec(&file).unwrap());
+
+        let mut pretty = vec![];
+        file.to_writer_pretty(&mut pretty).unwrap();
+        assert_eq!(pretty, serde_json::to_vec_pretty(&file).unwrap());
+        assert_eq!(ExcalidrawFile::from_reader(&pretty[..]).unwrap(), file);
+    }
+
+    #[test]
+    fn dark_theme() {
+        let json = serde_json::to_value(ExcalidrawFile::dark()).unwrap();
+        assert_eq!(json["appState"]["theme"], "dark");
+        assert_eq!(ExcalidrawFile::default().app_state.theme, Theme::Light);
+    }
+
+    #[test]
+    fn fill_styles_round_trip() {
+        let styles = [
+            (FillStyle::Hachure, "hachure"),
+            (FillStyle::CrossHatch, "cross-hatch"),
+            (FillStyle::Solid, "solid"),
+        ];
+        for (style, name) in styles {
+            let file = ExcalidrawFile {
+                elements: vec![Diamond::new(0., 0., 10., 10.).fill_style(style).build()],
+                ..Default::default()
+            };
+            let json = serde_json::to_string(&file).unwrap();
+            assert!(json.contains(&format!(r#""fillStyle":"{}""#, name)));
+            let file: ExcalidrawFile = json.parse().unwrap();
+            assert_eq!(file.elements[0].fill_style, style);
+        }
+    }
+
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn index_is_optional() {
+        let mut file: ExcalidrawFile =
+            r#"{"elements": [{"type": "ellipse", "id": "e", "index": "a5"}]}"#
+                .parse()
+                .unwrap();
+        assert_eq!(file.elements[0].index.as_deref(), Some("a5"));
+        file.elements[0].index = None;
+        let json = serde_json::to_value(&file).unwrap();
+        assert!(json["elements"][0].get("index").is_none());
+    }
+
+    #[test]
+    fn custom_data_round_trips() {
+        let mut element = Element::new("e", ElementKind::Ellipse, 0., 0., 10., 10.);
+        let json = serde_json::to_value(&element).unwrap();
+        assert!(json.get("customData").is_none());
+
+        element.custom_data = Some(serde_json::json!({"cell": [3, 0], "tags": ["disc", null]}));
+        let json = serde_json::to_value(&element).unwrap();
+        assert_eq!(json["customData"]["cell"][0], 3);
+        let back: Element = serde_json::from_value(json).unwrap();
+        assert_eq!(back, element);
+    }
+
+    #[test]
+    fn missing_fields_get_defaults() {
+        let file: ExcalidrawFile =
+            r#"{"elements": [{"type": "ellipse", "id": "e"}]}"#.parse().unwrap();
+        assert_eq!(file.r#type, "excalidraw");
+        assert_eq!(file.app_state, AppState::default());
+        assert_eq!(
+            file.elements[0],
+            Element {
+                seed: file.elements[0].seed,
+                ..Element::new("e", ElementKind::Ellipse, 0., 0., 0., 0.)
+            }
+        );
+    }
+
+    #[test]
+    fn serialize_rectangle() {
+        let file = ExcalidrawFile {
+            elements: vec![Element::new(
+                "a",
+                ElementKind::Rectangle,
+                10.,
+                20.,
+                30.,
+                40.,
+            )],
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&file).unwrap();
+        let element = &json["elements"][0];
+        assert_eq!(element["type"], "rectangle");
+        assert_eq!(element["id"], "a");
+        assert_eq!(element["x"], 10.);
+        assert_eq!(element["height"], 40.);
+        assert_eq!(element["strokeColor"], "#1e1e1e");
+        assert_eq!(element["backgroundColor"], "transparent");
+        assert_eq!(element["fillStyle"], "solid");
+        assert_eq!(element["angle"], 0.);
+        assert_eq!(element["opacity"], 100.);
+        assert_eq!(element["strokeStyle"], "solid");
+        assert_eq!(element["locked"], false);
+        assert_eq!(json["type"], "excalidraw");
+        assert_eq!(json["appState"]["viewBackgroundColor"], "#ffffff");
+    }
+
+    #[test]
+    fn serialize_text_and_arrow() {
+        let text = Element::new(
+            "t",
+            ElementKind::Text(TextElement::new("hi")),
+            0.,
+            0.,
+            20.,
+            25.,
+        );
+        let json = serde_json::to_value(&text).unwrap();
+        assert_eq!(json["type"], "text");
+        assert_eq!(json["text"], "hi");
+        assert_eq!(json["fontSize"], 20.);
+
+        let arrow = Element::new(
+            "a",
+            ElementKind::Arrow {
+                points: vec![[0., 0.], [100., 50.]],
+                start_binding: None,
+                end_binding: None,
+            },
+            0.,
+            0.,
+            100.,
+            50.,
+        );
+        let json = serde_json::to_value(&arrow).unwrap();
+        assert_eq!(json["type"], "arrow");
+        assert_eq!(json["points"][1][0], 100.);
     }
 }
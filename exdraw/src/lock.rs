@@ -0,0 +1,29 @@
+use crate::ExcalidrawFile;
+
+/// Locks every element in the file, so a generated template can be drawn on without moving it.
+pub fn lock_all(file: &mut ExcalidrawFile) {
+    for element in &mut file.elements {
+        element.locked = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ellipse, Rectangle};
+
+    #[test]
+    fn locks_everything() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 100.).locked(true).build(),
+                Ellipse::new(10., 10., 20., 20.).build(),
+            ],
+            ..Default::default()
+        };
+        lock_all(&mut file);
+        assert!(file.elements.iter().all(|element| element.locked));
+        let json = serde_json::to_value(&file).unwrap();
+        assert_eq!(json["elements"][1]["locked"], true);
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::{id::random_id, order::assign_indices, ElementKind, ExcalidrawFile};
+
+impl ExcalidrawFile {
+    /// Adds everything in `other` on top of this scene. `other`'s elements and groups all get
+    /// new ids, and everything in it that refers to them is updated, so nothing clashes with
+    /// what's already here. Embedded images are keyed by their contents so those are shared.
+    /// Every element's `index` is renumbered after so `other`'s sort after this scene's.
+    pub fn merge(&mut self, other: ExcalidrawFile) {
+        let mut ids: HashMap<String, String> = other
+            .elements
+            .iter()
+            .map(|element| (element.id.clone(), random_id()))
+            .collect();
+        for element in &other.elements {
+            for group in &element.group_ids {
+                ids.entry(group.clone()).or_insert_with(random_id);
+            }
+        }
+        // References to something that isn't in `other` are left alone.
+        let remap = |id: &mut String| {
+            if let Some(new) = ids.get(id) {
+                *id = new.clone();
+            }
+        };
+
+        for mut element in other.elements {
+            remap(&mut element.id);
+            element.group_ids.iter_mut().for_each(remap);
+            element.frame_id.iter_mut().for_each(remap);
+            for bound in &mut element.bound_elements {
+                remap(&mut bound.id);
+            }
+            match &mut element.kind {
+                ElementKind::Arrow {
+                    start_binding,
+                    end_binding,
+                    ..
+                } => {
+                    for binding in [start_binding, end_binding].into_iter().flatten() {
+                        remap(&mut binding.element_id);
+                    }
+                }
+                ElementKind::Text(text) => text.container_id.iter_mut().for_each(remap),
+                _ => {}
+            }
+            self.elements.push(element);
+        }
+        self.files.extend(other.files);
+        assign_indices(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bind_arrow, group, Rectangle};
+
+    fn piece() -> ExcalidrawFile {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(200., 0., 100., 50.).id("b").build(),
+            ],
+            ..Default::default()
+        };
+        bind_arrow(&mut file, "a", "b").unwrap();
+        group(&mut file, &["a", "b"]).unwrap();
+        file
+    }
+
+    #[test]
+    fn both_copies_survive() {
+        let mut first = piece();
+        assign_indices(&mut first);
+        let mut file = first.clone();
+        let mut second = piece();
+        assign_indices(&mut second);
+        file.merge(second);
+
+        assert_eq!(file.elements.len(), 6);
+        assert_eq!(file.elements[..3], first.elements[..]);
+        // The merged elements had the same indices as the first three.
+        assert!(file.elements.windows(2).all(|w| w[0].index < w[1].index));
+        assert_eq!(file.validate(), Ok(()));
+
+        let merged = &file.elements[3..];
+        let (a, b, arrow) = (&merged[0], &merged[1], &merged[2]);
+        assert!(!["a", "b"].contains(&a.id.as_str()));
+        assert_ne!(a.group_ids, first.elements[0].group_ids);
+        assert_eq!(a.group_ids, b.group_ids);
+        assert_eq!(a.bound_elements[0].id, arrow.id);
+        let ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } = &arrow.kind
+        else {
+            panic!("not an arrow");
+        };
+        assert_eq!(start_binding.as_ref().unwrap().element_id, a.id);
+        assert_eq!(end_binding.as_ref().unwrap().element_id, b.id);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::ExcalidrawFile;
+
+// Elements are drawn in the order they're in `elements`, so the last one is on top. Newer
+// versions of Excalidraw go by `Element::index` instead, so the helpers here renumber it to match.
+
+// The digits of fractional indices, in the order they sort in.
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// The `n`th fractional index with nothing after the integer part, the ones Excalidraw gives a
+// new scene. The first letter says how many digits follow, `a` is one and `b` two, so `a0` to
+// `az` come first and then `b00`.
+fn index_key(mut n: usize) -> String {
+    let base = DIGITS.len();
+    let mut digits = 1;
+    while n >= base.pow(digits as u32) {
+        n -= base.pow(digits as u32);
+        digits += 1;
+    }
+    let mut key = vec![b'a' + digits as u8 - 1];
+    key.extend(
+        (0..digits)
+            .rev()
+            .map(|i| DIGITS[n / base.pow(i as u32) % base]),
+    );
+    String::from_utf8(key).unwrap()
+}
+
+/// Gives every element a fractional index that sorts in the order they're in `elements`, `a0`,
+/// `a1` and so on. Call it after building a scene so it's drawn the same way in every version of
+/// Excalidraw.
+pub fn assign_indices(file: &mut ExcalidrawFile) {
+    for (i, element) in file.elements.iter_mut().enumerate() {
+        element.index = Some(index_key(i));
+    }
+}
+
+/// Moves the element with this id to the end of `elements`, so it's drawn over everything else.
+/// Returns `None` if it isn't in the file.
+pub fn bring_to_front(file: &mut ExcalidrawFile, id: &str) -> Option<()> {
+    let index = file.elements.iter().position(|element| element.id == id)?;
+    let element = file.elements.remove(index);
+    file.elements.push(element);
+    assign_indices(file);
+    Some(())
+}
+
+/// Moves the element with this id to the start of `elements`, so it's drawn under everything
+/// else. Returns `None` if it isn't in the file.
+pub fn send_to_back(file: &mut ExcalidrawFile, id: &str) -> Option<()> {
+    let index = file.elements.iter().position(|element| element.id == id)?;
+    let element = file.elements.remove(index);
+    file.elements.insert(0, element);
+    assign_indices(file);
+    Some(())
+}
+
+/// Rearranges the elements with these ids so they're drawn in this order, first at the bottom.
+/// They swap around between the places they're already in, everything else stays where it is.
+/// Returns `None` without changing anything if one of the ids isn't in the file or is listed
+/// twice.
+pub fn reorder(file: &mut ExcalidrawFile, ids: &[&str]) -> Option<()> {
+    let slots: Vec<_> = file
+        .elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| ids.contains(&element.id.as_str()))
+        .map(|(i, _)| i)
+        .collect();
+    if slots.len() != ids.len() {
+        return None;
+    }
+    let mut reordered: Vec<_> = slots.iter().map(|&i| file.elements[i].clone()).collect();
+    reordered.sort_by_key(|element| ids.iter().position(|id| *id == element.id));
+    for (slot, element) in slots.into_iter().zip(reordered) {
+        file.elements[slot] = element;
+    }
+    assign_indices(file);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rectangle;
+
+    fn scene() -> ExcalidrawFile {
+        ExcalidrawFile {
+            elements: ["a", "b", "c", "d"]
+                .into_iter()
+                .map(|id| Rectangle::new(0., 0., 10., 10.).id(id).build())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn ids(file: &ExcalidrawFile) -> Vec<&str> {
+        file.elements
+            .iter()
+            .map(|element| element.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn front_and_back() {
+        let mut file = scene();
+        bring_to_front(&mut file, "b").unwrap();
+        assert_eq!(ids(&file), ["a", "c", "d", "b"]);
+        send_to_back(&mut file, "d").unwrap();
+        assert_eq!(ids(&file), ["d", "a", "c", "b"]);
+        assert_eq!(bring_to_front(&mut file, "nope"), None);
+    }
+
+    #[test]
+    fn indices_sort_like_the_elements() {
+        let keys: Vec<_> = (0..5000).map(index_key).collect();
+        assert_eq!(keys[..3], ["a0", "a1", "a2"]);
+        assert_eq!(keys[61..63], ["az", "b00"]);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut file = scene();
+        assert!(file.elements.iter().all(|element| element.index.is_none()));
+        bring_to_front(&mut file, "a").unwrap();
+        let indices: Vec<_> = file
+            .elements
+            .iter()
+            .map(|element| element.index.as_deref().unwrap())
+            .collect();
+        assert_eq!(indices, ["a0", "a1", "a2", "a3"]);
+        assert_eq!(file.element("a").unwrap().index.as_deref(), Some("a3"));
+    }
+
+    #[test]
+    fn reorder_in_place() {
+        let mut file = scene();
+        reorder(&mut file, &["d", "b"]).unwrap();
+        assert_eq!(ids(&file), ["a", "d", "c", "b"]);
+        reorder(&mut file, &["c", "b", "a", "d"]).unwrap();
+        assert_eq!(ids(&file), ["c", "b", "a", "d"]);
+
+        assert_eq!(reorder(&mut file, &["a", "nope"]), None);
+        assert_eq!(reorder(&mut file, &["a", "a"]), None);
+        assert_eq!(ids(&file), ["c", "b", "a", "d"]);
+    }
+}
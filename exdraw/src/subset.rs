@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use crate::{ElementKind, ExcalidrawFile};
+
+impl ExcalidrawFile {
+    /// A new file with just the elements with these ids, like copying them and pasting them into
+    /// an empty drawing. A selected frame brings its contents along, and shapes bring their text
+    /// and the arrows bound to them. Bindings, containers and frames outside the copy are
+    /// dropped, so an arrow to a shape that isn't copied is left loose. Images keep their data
+    /// from `files` and the editor settings are copied. Ids that aren't in the file are ignored.
+    pub fn subset(&self, ids: &[&str]) -> ExcalidrawFile {
+        let mut keep: HashSet<&str> = ids
+            .iter()
+            .filter_map(|id| Some(self.element(id)?.id.as_str()))
+            .collect();
+        for element in &self.elements {
+            if element
+                .frame_id
+                .as_deref()
+                .is_some_and(|frame| ids.contains(&frame))
+            {
+                keep.insert(&element.id);
+            }
+        }
+        // Shapes list their text and arrows and arrows list their text, so this stops once the
+        // arrows' text is in.
+        loop {
+            let bound: Vec<_> = self
+                .elements
+                .iter()
+                .filter(|element| keep.contains(element.id.as_str()))
+                .flat_map(|element| &element.bound_elements)
+                .filter_map(|bound| Some(self.element(&bound.id)?.id.as_str()))
+                .filter(|id| !keep.contains(id))
+                .collect();
+            if bound.is_empty() {
+                break;
+            }
+            keep.extend(bound);
+        }
+
+        let kept = |id: &Option<String>| id.as_deref().is_some_and(|id| keep.contains(id));
+        let mut elements = vec![];
+        for element in &self.elements {
+            if !keep.contains(element.id.as_str()) {
+                continue;
+            }
+            let mut element = element.clone();
+            match &mut element.kind {
+                ElementKind::Arrow {
+                    start_binding,
+                    end_binding,
+                    ..
+                } => {
+                    for binding in [start_binding, end_binding] {
+                        if binding
+                            .as_ref()
+                            .is_some_and(|binding| !keep.contains(binding.element_id.as_str()))
+                        {
+                            *binding = None;
+                        }
+                    }
+                }
+                ElementKind::Text(text) if !kept(&text.container_id) => text.container_id = None,
+                _ => {}
+            }
+            if !kept(&element.frame_id) {
+                element.frame_id = None;
+            }
+            element
+                .bound_elements
+                .retain(|bound| keep.contains(bound.id.as_str()));
+            elements.push(element);
+        }
+
+        let images: HashSet<_> = elements
+            .iter()
+            .filter_map(|element| match &element.kind {
+                ElementKind::Image { file_id, .. } => Some(file_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        let files = self
+            .files
+            .iter()
+            .filter(|(id, _)| images.contains(id.as_str()))
+            .map(|(id, data)| (id.clone(), data.clone()))
+            .collect();
+
+        ExcalidrawFile {
+            r#type: self.r#type.clone(),
+            version: self.version,
+            source: self.source.clone(),
+            elements,
+            app_state: self.app_state.clone(),
+            files,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        add_frame, add_image, add_label, bind_arrow, BoundElement, BoundElementKind, ElementKind,
+        ExcalidrawFile, ImageFormat, Rectangle, Text,
+    };
+
+    #[test]
+    fn copies_what_the_selection_needs() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(300., 0., 100., 50.).id("b").build(),
+                Rectangle::new(600., 0., 100., 50.).id("c").build(),
+            ],
+            ..Default::default()
+        };
+        let label = add_label(&mut file, "a", "hi").unwrap();
+        let ab = bind_arrow(&mut file, "a", "b").unwrap();
+        let bc = bind_arrow(&mut file, "b", "c").unwrap();
+        // `add_label` only labels shapes, but arrows can have text too.
+        let mut text = Text::new(200., 0., "to b").build();
+        if let ElementKind::Text(text) = &mut text.kind {
+            text.container_id = Some(ab.clone());
+        }
+        let arrow_label = text.id.clone();
+        file.elements.push(text);
+        file.element_mut(&ab)
+            .unwrap()
+            .bound_elements
+            .push(BoundElement {
+                id: arrow_label.clone(),
+                r#type: BoundElementKind::Text,
+            });
+        let image = add_image(&mut file, b"not really an image", ImageFormat::Png);
+        add_image(&mut file, b"another one", ImageFormat::Png);
+        let frame = add_frame(&mut file, "Frame", &[&image]).unwrap();
+
+        let subset = file.subset(&["a", &frame, "missing"]);
+        let ids: Vec<_> = subset.elements.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["a", &label, &ab, &arrow_label, &image, &frame]);
+        assert_eq!(subset.validate(), Ok(()));
+        assert_eq!(subset.files.len(), 1);
+        assert_eq!(subset.app_state, file.app_state);
+
+        let ElementKind::Arrow {
+            start_binding,
+            end_binding,
+            ..
+        } = &subset.element(&ab).unwrap().kind
+        else {
+            panic!("not an arrow");
+        };
+        assert_eq!(start_binding.as_ref().unwrap().element_id, "a");
+        assert_eq!(*end_binding, None);
+        assert_eq!(subset.element("a").unwrap().bound_elements.len(), 2);
+
+        // Text on its own loses its container, and `b` brings both its arrows.
+        let subset = file.subset(&[&label, "b"]);
+        let ElementKind::Text(text) = &subset.element(&label).unwrap().kind else {
+            panic!("not text");
+        };
+        assert_eq!(text.container_id, None);
+        assert!(subset.element(&bc).is_some());
+        assert_eq!(subset.elements.len(), 5);
+        assert_eq!(subset.validate(), Ok(()));
+    }
+}
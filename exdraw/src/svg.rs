@@ -0,0 +1,371 @@
+use std::fmt::Write;
+
+use crate::{
+    BoundingBox, Element, ElementKind, ExcalidrawFile, FontFamily, StrokeStyle, TextAlign,
+    TextElement, Theme,
+};
+
+// What Excalidraw does to the canvas in dark mode. Images are turned back so they look normal.
+const DARK_MODE_STYLE: &str = "<style>svg { filter: invert(93%) hue-rotate(180deg); } \
+image { filter: invert(100%) hue-rotate(180deg); }</style>";
+
+// Space around the drawing.
+const PADDING: f64 = 10.;
+// Length of the two strokes of an arrowhead and the angle between each and the shaft.
+const ARROWHEAD_LENGTH: f64 = 15.;
+const ARROWHEAD_ANGLE: f64 = std::f64::consts::PI / 6.;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Colors come straight from the file, so they're escaped like everything else from it. A file
+// that isn't valid could have anything in them.
+fn paint(color: &str) -> String {
+    if color == "transparent" {
+        "none".to_string()
+    } else {
+        escape(color)
+    }
+}
+
+fn font_family(family: FontFamily) -> &'static str {
+    match family {
+        FontFamily::Virgil => "Virgil, Segoe UI Emoji",
+        FontFamily::Helvetica => "Helvetica, sans-serif",
+        FontFamily::Cascadia | FontFamily::Other(_) => "Cascadia, monospace",
+    }
+}
+
+fn points_attribute(element: &Element, points: &[[f64; 2]]) -> String {
+    points
+        .iter()
+        .map(|[x, y]| format!("{},{}", element.x + x, element.y + y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The two strokes of an arrowhead at the last point, pointing away from the point before it.
+fn arrowhead(element: &Element, points: &[[f64; 2]]) -> Option<String> {
+    let [.., [x0, y0], [x1, y1]] = points else {
+        return None;
+    };
+    let direction = (y1 - y0).atan2(x1 - x0);
+    let length = ARROWHEAD_LENGTH.min((x1 - x0).hypot(y1 - y0) / 2.);
+    let (tip_x, tip_y) = (element.x + x1, element.y + y1);
+    let barb = |side: f64| {
+        let angle = direction + std::f64::consts::PI + side * ARROWHEAD_ANGLE;
+        (tip_x + length * angle.cos(), tip_y + length * angle.sin())
+    };
+    let (left_x, left_y) = barb(1.);
+    let (right_x, right_y) = barb(-1.);
+    Some(format!(
+        "{},{} {},{} {},{}",
+        left_x, left_y, tip_x, tip_y, right_x, right_y
+    ))
+}
+
+fn text(svg: &mut String, element: &Element, text: &TextElement, attributes: &str) {
+    let (anchor, x) = match text.text_align {
+        TextAlign::Left => ("start", element.x),
+        TextAlign::Center => ("middle", element.x + element.width / 2.),
+        TextAlign::Right => ("end", element.x + element.width),
+    };
+    let line_height = text.font_size * text.line_height;
+    write!(
+        svg,
+        r#"<text x="{}" y="{}" font-family="{}" font-size="{}" text-anchor="{}" fill="{}"{}>"#,
+        x,
+        element.y,
+        font_family(text.font_family),
+        text.font_size,
+        anchor,
+        paint(&element.stroke_color),
+        attributes
+    )
+    .unwrap();
+    for line in text.text.split('\n') {
+        // `dy` moves each line down from the last, the first one by a line so its top is at `y`.
+        write!(
+            svg,
+            r#"<tspan x="{}" dy="{}">{}</tspan>"#,
+            x,
+            line_height,
+            escape(line)
+        )
+        .unwrap();
+    }
+    svg.push_str("</text>");
+}
+
+fn element(svg: &mut String, file: &ExcalidrawFile, element: &Element) {
+    let (x, y, width, height) = (element.x, element.y, element.width, element.height);
+    let mut attributes = String::new();
+    if element.opacity < 100. {
+        write!(attributes, r#" opacity="{}""#, element.opacity / 100.).unwrap();
+    }
+    if element.angle != 0. {
+        write!(
+            attributes,
+            r#" transform="rotate({} {} {})""#,
+            element.angle.to_degrees(),
+            x + width / 2.,
+            y + height / 2.
+        )
+        .unwrap();
+    }
+    let mut stroke = format!(
+        r#"stroke="{}" stroke-width="{}""#,
+        paint(&element.stroke_color),
+        element.stroke_width
+    );
+    // The same dash lengths Excalidraw uses.
+    match element.stroke_style {
+        StrokeStyle::Solid => {}
+        StrokeStyle::Dashed => write!(
+            stroke,
+            r#" stroke-dasharray="8 {}""#,
+            8. + element.stroke_width
+        )
+        .unwrap(),
+        StrokeStyle::Dotted => write!(
+            stroke,
+            r#" stroke-dasharray="1.5 {}" stroke-linecap="round""#,
+            6. + element.stroke_width
+        )
+        .unwrap(),
+    }
+    let shape = format!(
+        r#"{} fill="{}"{}"#,
+        stroke,
+        paint(&element.background_color),
+        attributes
+    );
+    let outline = format!(r#"{} fill="none"{}"#, stroke, attributes);
+    match &element.kind {
+        ElementKind::Rectangle => write!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            x, y, width, height, shape
+        ),
+        ElementKind::Ellipse => write!(
+            svg,
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" {}/>"#,
+            x + width / 2.,
+            y + height / 2.,
+            width / 2.,
+            height / 2.,
+            shape
+        ),
+        ElementKind::Diamond => write!(
+            svg,
+            r#"<polygon points="{},{} {},{} {},{} {},{}" {}/>"#,
+            x + width / 2.,
+            y,
+            x + width,
+            y + height / 2.,
+            x + width / 2.,
+            y + height,
+            x,
+            y + height / 2.,
+            shape
+        ),
+        ElementKind::Line { points } => write!(
+            svg,
+            r#"<polyline points="{}" {}/>"#,
+            points_attribute(element, points),
+            outline
+        ),
+        ElementKind::Arrow { points, .. } => {
+            write!(
+                svg,
+                r#"<polyline points="{}" {}/>"#,
+                points_attribute(element, points),
+                outline
+            )
+            .unwrap();
+            match arrowhead(element, points) {
+                Some(head) => write!(svg, r#"<polyline points="{}" {}/>"#, head, outline),
+                None => Ok(()),
+            }
+        }
+        ElementKind::Text(text_element) => {
+            text(svg, element, text_element, &attributes);
+            Ok(())
+        }
+        ElementKind::Image { file_id, .. } => {
+            match file.files.get(file_id).and_then(|file| file["dataURL"].as_str()) {
+                Some(data_url) => write!(
+                    svg,
+                    r#"<image x="{}" y="{}" width="{}" height="{}" href="{}"{}/>"#,
+                    x,
+                    y,
+                    width,
+                    height,
+                    escape(data_url),
+                    attributes
+                ),
+                None => Ok(()),
+            }
+        }
+        ElementKind::Frame { name } => {
+            write!(
+                svg,
+                r##"<rect x="{}" y="{}" width="{}" height="{}" stroke="#bbb" fill="none"/>"##,
+                x, y, width, height
+            )
+            .unwrap();
+            match name {
+                Some(name) => write!(
+                    svg,
+                    r##"<text x="{}" y="{}" font-family="Helvetica, sans-serif" font-size="14" fill="#999">{}</text>"##,
+                    x,
+                    y - 4.,
+                    escape(name)
+                ),
+                None => Ok(()),
+            }
+        }
+        ElementKind::Other(_) => Ok(()),
+    }
+    .unwrap();
+}
+
+impl ExcalidrawFile {
+    /// Draws the scene as an SVG, for previewing files without Excalidraw. Shapes are drawn with
+    /// clean lines instead of the hand drawn look and every fill is solid. Dark mode is drawn
+    /// the way Excalidraw draws it, by inverting the colors. Element types this crate doesn't
+    /// model, `ElementKind::Other`, are left out.
+    pub fn to_svg(&self) -> String {
+        let bounds = self.bounding_box().unwrap_or(BoundingBox {
+            min_x: 0.,
+            min_y: 0.,
+            max_x: 0.,
+            max_y: 0.,
+        });
+        let bounds = bounds.expand(PADDING);
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+            bounds.min_x,
+            bounds.min_y,
+            bounds.width(),
+            bounds.height(),
+            bounds.width(),
+            bounds.height()
+        )
+        .unwrap();
+        if self.app_state.theme == Theme::Dark {
+            svg.push_str(DARK_MODE_STYLE);
+        }
+        write!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            bounds.min_x,
+            bounds.min_y,
+            bounds.width(),
+            bounds.height(),
+            paint(&self.app_state.view_background_color)
+        )
+        .unwrap();
+        for e in self.elements.iter().filter(|e| !e.is_deleted) {
+            element(&mut svg, self, e);
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arrow, Color, Ellipse, Line, Rectangle, Text};
+
+    #[test]
+    fn draws_each_element() {
+        let file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.)
+                    .stroke_color(Color::BLUE)
+                    .background(Color::LIGHT_BLUE)
+                    .build(),
+                Ellipse::new(200., 0., 40., 20.).build(),
+                Line::new(&[[0., 100.], [50., 120.]]).build(),
+                Arrow::new([0., 200.], [100., 200.]).build(),
+                Text::new(0., 300., "a < b\nc").build(),
+            ],
+            ..Default::default()
+        };
+        let svg = file.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(
+            r##"<rect x="0" y="0" width="100" height="50" stroke="#1971c2" stroke-width="1" fill="#a5d8ff"/>"##
+        ));
+        assert!(svg.contains(r#"<ellipse cx="220" cy="10" rx="20" ry="10""#));
+        assert!(svg.contains(r#"<polyline points="0,100 50,120""#));
+        // The arrow's shaft and head.
+        assert_eq!(svg.matches("<polyline").count(), 3);
+        assert!(svg.contains(">a &lt; b</tspan>"));
+        assert!(svg.contains(">c</tspan>"));
+        // The background covers the drawing and the padding around it.
+        assert!(svg.contains(r##"<rect x="-10" y="-10" width="260" "##));
+    }
+
+    #[test]
+    fn empty_scene() {
+        let svg = ExcalidrawFile::default().to_svg();
+        assert!(svg.contains(r#"viewBox="-10 -10 20 20""#));
+    }
+
+    #[test]
+    fn dark_mode_inverts() {
+        assert!(!ExcalidrawFile::default().to_svg().contains("invert"));
+        let svg = ExcalidrawFile::dark().to_svg();
+        assert!(svg.contains("filter: invert(93%) hue-rotate(180deg)"));
+        assert!(svg.contains(r##"fill="#ffffff""##));
+    }
+
+    #[test]
+    fn dashed_strokes() {
+        let file = ExcalidrawFile {
+            elements: vec![
+                Arrow::new([0., 0.], [100., 0.])
+                    .stroke_style(StrokeStyle::Dashed)
+                    .stroke_width(2.)
+                    .build(),
+                Rectangle::new(0., 0., 10., 10.)
+                    .stroke_style(StrokeStyle::Dotted)
+                    .build(),
+            ],
+            ..Default::default()
+        };
+        let svg = file.to_svg();
+        assert_eq!(svg.matches(r#"stroke-dasharray="8 10""#).count(), 2);
+        assert!(svg.contains(r#"stroke-dasharray="1.5 7""#));
+    }
+
+    #[test]
+    fn colors_cant_inject_markup() {
+        let attack = r#""/><script>alert(1)</script><rect fill=""#;
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 10., 10.).build(),
+                Text::new(0., 20., "hi").build(),
+            ],
+            ..Default::default()
+        };
+        for element in &mut file.elements {
+            element.stroke_color = attack.into();
+            element.background_color = attack.into();
+        }
+        file.app_state.view_background_color = attack.into();
+        let svg = file.to_svg();
+        assert!(!svg.contains("<script"));
+        assert!(svg.contains("&lt;script&gt;"));
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Excalidraw stores the font as a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FontFamily {
+    /// The hand drawn font.
+    #[default]
+    Virgil,
+    Helvetica,
+    /// Monospace.
+    Cascadia,
+    /// One of the fonts newer versions of Excalidraw added, by its code.
+    Other(u8),
+}
+
+impl FontFamily {
+    pub fn code(self) -> u8 {
+        match self {
+            FontFamily::Virgil => 1,
+            FontFamily::Helvetica => 2,
+            FontFamily::Cascadia => 3,
+            FontFamily::Other(code) => code,
+        }
+    }
+
+    // Average width of a character as a fraction of the font size.
+    fn char_width(self) -> f64 {
+        match self {
+            FontFamily::Virgil => 0.55,
+            FontFamily::Helvetica => 0.5,
+            FontFamily::Cascadia | FontFamily::Other(_) => 0.6,
+        }
+    }
+}
+
+impl Serialize for FontFamily {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for FontFamily {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match u8::deserialize(deserializer)? {
+            1 => FontFamily::Virgil,
+            2 => FontFamily::Helvetica,
+            3 => FontFamily::Cascadia,
+            code => FontFamily::Other(code),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// The text specific fields of a text element.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TextElement {
+    /// Can have newlines, each line is drawn under the last.
+    pub text: String,
+    pub font_size: f64,
+    pub font_family: FontFamily,
+    pub text_align: TextAlign,
+    pub vertical_align: VerticalAlign,
+    /// Height of a line as a multiple of the font size.
+    pub line_height: f64,
+    /// The shape the text is inside of, if it's a label.
+    pub container_id: Option<String>,
+}
+
+impl Default for TextElement {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl TextElement {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            font_size: 20.,
+            font_family: FontFamily::default(),
+            text_align: TextAlign::default(),
+            vertical_align: VerticalAlign::default(),
+            line_height: 1.25,
+            container_id: None,
+        }
+    }
+
+    /// Estimates the width and height of the text. Excalidraw measures text properly when it's
+    /// edited, until then this is close enough to lay things out.
+    pub fn measure(&self) -> (f64, f64) {
+        let lines = self.text.split('\n');
+        let longest = lines.clone().map(|line| line.chars().count()).max();
+        let width = longest.unwrap_or(0) as f64 * self.font_size * self.font_family.char_width();
+        let height = lines.count() as f64 * self.font_size * self.line_height;
+        (width, height)
+    }
+
+    /// Breaks lines at spaces so none of them is wider than `width`, as far as that's possible
+    /// without breaking up words. Newlines that are already there are kept.
+    pub fn wrap(&mut self, width: f64) {
+        let per_line = (width / (self.font_size * self.font_family.char_width())).max(1.) as usize;
+        let lines: Vec<_> = self
+            .text
+            .split('\n')
+            .map(|line| {
+                let mut wrapped = String::new();
+                let mut length = 0;
+                for word in line.split(' ') {
+                    let word_length = word.chars().count();
+                    if length > 0 {
+                        if length + 1 + word_length > per_line {
+                            wrapped.push('\n');
+                            length = 0;
+                        } else {
+                            wrapped.push(' ');
+                            length += 1;
+                        }
+                    }
+                    wrapped.push_str(word);
+                    length += word_length;
+                }
+                wrapped
+            })
+            .collect();
+        self.text = lines.join("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_line_text_is_taller() {
+        let one = TextElement::new("hello");
+        let two = TextElement::new("hello\nworld");
+        let (one_width, one_height) = one.measure();
+        let (two_width, two_height) = two.measure();
+        assert_eq!(one_width, two_width);
+        assert_eq!(two_height, 2. * one_height);
+        assert_eq!(one_height, 25.);
+    }
+
+    #[test]
+    fn wrap_at_spaces() {
+        // A character of Virgil at 20 is 11 wide, so 10 fit in 111.
+        let mut text = TextElement::new("the quick brown fox\njumps over the lazy dog");
+        text.wrap(111.);
+        assert_eq!(text.text, "the quick\nbrown fox\njumps over\nthe lazy\ndog");
+        assert!(text.measure().0 <= 111.);
+
+        let mut word = TextElement::new("incomprehensible");
+        word.wrap(50.);
+        assert_eq!(word.text, "incomprehensible");
+    }
+
+    #[test]
+    fn serialize() {
+        let text = TextElement {
+            font_family: FontFamily::Cascadia,
+            text_align: TextAlign::Center,
+            vertical_align: VerticalAlign::Middle,
+            ..TextElement::new("hi")
+        };
+        let json = serde_json::to_value(text).unwrap();
+        assert_eq!(json["fontFamily"], 3);
+        assert_eq!(json["textAlign"], "center");
+        assert_eq!(json["verticalAlign"], "middle");
+        assert_eq!(json["lineHeight"], 1.25);
+    }
+}
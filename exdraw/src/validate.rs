@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::{color::is_color, BoundElementKind, ElementKind, ExcalidrawFile};
+
+/// Something about a file that Excalidraw won't load or will quietly get wrong.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    #[error("More than one element has the id `{0}`.")]
+    DuplicateId(String),
+    #[error("Arrow `{arrow}` is bound to `{element}`, which isn't in the file.")]
+    MissingBinding { arrow: String, element: String },
+    #[error("Text `{text}` is in container `{container}`, which isn't in the file.")]
+    MissingContainer { text: String, container: String },
+    #[error("`{container}` lists `{text}` as its text but it isn't text with that container.")]
+    TextNotInContainer { container: String, text: String },
+    #[error("Element `{element}` is in frame `{frame}`, which isn't a frame in the file.")]
+    MissingFrame { element: String, frame: String },
+    #[error("Element `{element}` has the color `{color}`, which isn't a hex color.")]
+    BadColor { element: String, color: String },
+    #[error("Element `{0}` has an empty link.")]
+    EmptyLink(String),
+    #[error("Element `{element}` has an opacity of {opacity}, which isn't from 0 to 100.")]
+    BadOpacity { element: String, opacity: f64 },
+}
+
+impl ExcalidrawFile {
+    /// Checks that ids are unique, that everything elements refer to by id is there, that
+    /// colors are hex colors, that opacities are percentages and that links aren't empty.
+    /// Returns every problem it finds, in the order of the elements.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        let mut seen = HashSet::new();
+        for element in &self.elements {
+            if !seen.insert(element.id.as_str()) {
+                errors.push(ValidationError::DuplicateId(element.id.clone()));
+            }
+        }
+
+        for element in &self.elements {
+            let id = &element.id;
+            for color in [&element.stroke_color, &element.background_color] {
+                if !is_color(color) {
+                    errors.push(ValidationError::BadColor {
+                        element: id.clone(),
+                        color: color.clone(),
+                    });
+                }
+            }
+            if !(0. ..=100.).contains(&element.opacity) {
+                errors.push(ValidationError::BadOpacity {
+                    element: id.clone(),
+                    opacity: element.opacity,
+                });
+            }
+            if element
+                .link
+                .as_ref()
+                .is_some_and(|link| link.trim().is_empty())
+            {
+                errors.push(ValidationError::EmptyLink(id.clone()));
+            }
+            match &element.kind {
+                ElementKind::Arrow {
+                    start_binding,
+                    end_binding,
+                    ..
+                } => {
+                    for binding in [start_binding, end_binding].into_iter().flatten() {
+                        if self.element(&binding.element_id).is_none() {
+                            errors.push(ValidationError::MissingBinding {
+                                arrow: id.clone(),
+                                element: binding.element_id.clone(),
+                            });
+                        }
+                    }
+                }
+                ElementKind::Text(text) => {
+                    if let Some(container) = &text.container_id {
+                        if self.element(container).is_none() {
+                            errors.push(ValidationError::MissingContainer {
+                                text: id.clone(),
+                                container: container.clone(),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+            for bound in &element.bound_elements {
+                if bound.r#type != BoundElementKind::Text {
+                    continue;
+                }
+                let contained = match self.element(&bound.id).map(|text| &text.kind) {
+                    Some(ElementKind::Text(text)) => text.container_id.as_ref() == Some(id),
+                    _ => false,
+                };
+                if !contained {
+                    errors.push(ValidationError::TextNotInContainer {
+                        container: id.clone(),
+                        text: bound.id.clone(),
+                    });
+                }
+            }
+            if let Some(frame) = &element.frame_id {
+                let is_frame = matches!(
+                    self.element(frame).map(|frame| &frame.kind),
+                    Some(ElementKind::Frame { .. })
+                );
+                if !is_frame {
+                    errors.push(ValidationError::MissingFrame {
+                        element: id.clone(),
+                        frame: frame.clone(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_frame, bind_arrow, BoundElement, Element, Rectangle, Text};
+
+    #[test]
+    fn valid_file() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(200., 0., 100., 50.)
+                    .id("b")
+                    .background("#A5D8FF".parse().unwrap())
+                    .build(),
+                Text::new(0., 100., "hi")
+                    .stroke_color("#abc".parse().unwrap())
+                    .link("https://excalidraw.com")
+                    .build(),
+            ],
+            ..Default::default()
+        };
+        bind_arrow(&mut file, "a", "b").unwrap();
+        add_frame(&mut file, "Frame", &["a", "b"]).unwrap();
+        assert_eq!(file.validate(), Ok(()));
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let mut file = ExcalidrawFile {
+            elements: vec![
+                Rectangle::new(0., 0., 100., 50.).id("a").build(),
+                Rectangle::new(200., 0., 100., 50.).id("b").build(),
+                // The builders won't take a bad color, but a file can have one.
+                Element {
+                    stroke_color: "#ff".into(),
+                    opacity: 150.,
+                    ..Rectangle::new(0., 0., 1., 1.).id("a").link(" ").build()
+                },
+                Text::new(0., 100., "hi").id("t").build(),
+            ],
+            ..Default::default()
+        };
+        let arrow = bind_arrow(&mut file, "a", "b").unwrap();
+        file.elements.retain(|e| e.id != "b");
+        file.elements[0].bound_elements.push(BoundElement {
+            id: "t".into(),
+            r#type: BoundElementKind::Text,
+        });
+        file.elements[0].frame_id = Some("t".into());
+
+        let errors = file.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                ValidationError::DuplicateId("a".into()),
+                ValidationError::TextNotInContainer {
+                    container: "a".into(),
+                    text: "t".into()
+                },
+                ValidationError::MissingFrame {
+                    element: "a".into(),
+                    frame: "t".into()
+                },
+                ValidationError::BadColor {
+                    element: "a".into(),
+                    color: "#ff".into()
+                },
+                ValidationError::BadOpacity {
+                    element: "a".into(),
+                    opacity: 150.
+                },
+                ValidationError::EmptyLink("a".into()),
+                ValidationError::MissingBinding {
+                    arrow,
+                    element: "b".into()
+                },
+            ]
+        );
+    }
+}